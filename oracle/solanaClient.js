@@ -44,7 +44,7 @@ class SolanaClient {
         }
     }
 
-    async verifyAgent(agentPublicKey, score, specialties) {
+    async verifyAgent(agentPublicKey, score, specialties, skills = 0) {
         console.log(`📝 Writing verification to chain for ${agentPublicKey.toString()}...`);
         
         try {
@@ -53,10 +53,19 @@ class SolanaClient {
                 this.program.programId
             );
 
+            const agent = await this.program.account.agentAccount.fetch(agentPublicKey);
+            const directory = agent.listed
+                ? PublicKey.findProgramAddressSync(
+                    [Buffer.from("directory"), new anchor.BN(agent.directoryPage).toArrayLike(Buffer, "le", 4)],
+                    this.program.programId
+                )[0]
+                : null;
+
             await this.program.methods
-                .verifyAgent(score, specialties)
+                .verifyAgent(score, specialties, new anchor.BN(skills))
                 .accounts({
                     agentAccount: agentPublicKey,
+                    directory,
                     registryConfig: configPda,
                     admin: this.provider.wallet.publicKey,
                 })
@@ -1,18 +1,27 @@
 require('dotenv').config();
 const anchor = require('@coral-xyz/anchor');
 const { PublicKey, Connection, Keypair } = require('@solana/web3.js');
-const { createHash } = require('crypto');
+const { createHash, createDecipheriv } = require('crypto');
 const https = require('https');
 const http = require('http');
 const fs = require('fs');
 const path = require('path');
 
 const ESCROW_IDL = require('./escrow-idl.json');
+const REGISTRY_IDL = require('./idl.json');
 const POLL_INTERVAL_MS = parseInt(process.env.POLL_INTERVAL_MS) || 15000;
 const ORACLE_TIMEOUT_HOURS = 48;
 const MAX_RETRIES = 3;
 const FETCH_TIMEOUT_MS = 10000;
 const MAX_RESPONSE_BYTES = 10 * 1024 * 1024; // 10MB
+// Full evaluation reports; only their sha256 goes on-chain with the verdict
+const REPORT_DIR = process.env.REPORT_DIR || path.join(__dirname, 'reports');
+// Content keys of encrypted deliveries, handed over by workers as <result PDA>.key (hex)
+const KEY_DIR = process.env.KEY_DIR || path.join(__dirname, 'keys');
+// Lower bounds (lamports) of task board tiers 1-3, mirroring BOUNTY_TIER_LIMITS
+const BOUNTY_TIER_LIMITS = [100_000_000n, 1_000_000_000n, 10_000_000_000n];
+// Entries per task board page, mirroring BOARD_PAGE_SIZE
+const BOARD_PAGE_SIZE = 128;
 
 // ─── SSRF protection: block internal/private IP ranges ───────────────────────
 const BLOCKED_CIDRS = [
@@ -100,6 +109,61 @@ function hashesMatch(fetchedBuffer, onChainHash) {
     return fetched.every((byte, i) => byte === onChainHash[i]);
 }
 
+// ─── Git deliverables ────────────────────────────────────────────────────────
+// Confirms the commit recorded on-chain exists with the recorded tree and, when a
+// PR number is given, that it is the PR's head. Only GitHub repositories for now.
+function gitObjectHex(id) {
+    const bytes = id.sha1 ? id.sha1[0] : id.sha256[0];
+    return Buffer.from(bytes).toString('hex');
+}
+
+async function checkGitDeliverable(git) {
+    const match = /^github\.com\/([\w.-]+)\/([\w.-]+)$/.exec(git.repository);
+    if (!match) {
+        console.log(`⚠️ Unsupported repository: ${git.repository}`);
+        return false;
+    }
+    const api = `https://api.github.com/repos/${match[1]}/${match[2]}`;
+    const commitSha = gitObjectHex(git.commit);
+
+    const commit = JSON.parse(
+        (await fetchWithRetry(`${api}/git/commits/${commitSha}`, MAX_RETRIES, FETCH_TIMEOUT_MS)).toString()
+    );
+    if (commit.tree.sha !== gitObjectHex(git.tree)) {
+        console.log(`❌ Tree mismatch for commit ${commitSha}`);
+        return false;
+    }
+
+    if (git.pullRequest !== null) {
+        const pr = JSON.parse(
+            (await fetchWithRetry(`${api}/pulls/${git.pullRequest}`, MAX_RETRIES, FETCH_TIMEOUT_MS)).toString()
+        );
+        if (pr.head.sha !== commitSha) {
+            console.log(`❌ PR #${git.pullRequest} head is ${pr.head.sha}, not ${commitSha}`);
+            return false;
+        }
+    }
+    return true;
+}
+
+// ─── Encrypted deliverables ──────────────────────────────────────────────────
+// The ciphertext at result_url is AES-256-GCM: iv (12 bytes) || data || tag (16 bytes).
+// The on-chain key blob is sealed to the poster, so the worker gives the oracle the
+// content key out of band; until it arrives the result is left pending.
+function loadContentKey(resultPda) {
+    const file = path.join(KEY_DIR, `${resultPda.toBase58()}.key`);
+    if (!fs.existsSync(file)) return null;
+    return Buffer.from(fs.readFileSync(file, 'utf8').trim(), 'hex');
+}
+
+function decryptDeliverable(ciphertext, key) {
+    const iv = ciphertext.subarray(0, 12);
+    const tag = ciphertext.subarray(ciphertext.length - 16);
+    const decipher = createDecipheriv('aes-256-gcm', key, iv);
+    decipher.setAuthTag(tag);
+    return Buffer.concat([decipher.update(ciphertext.subarray(12, ciphertext.length - 16)), decipher.final()]);
+}
+
 // ─── Basic task output evaluation ────────────────────────────────────────────
 // Replace with LLM-based eval for production
 function evaluateOutput(body) {
@@ -136,6 +200,11 @@ class TaskVerifier {
         const programId = new PublicKey(process.env.ESCROW_PROGRAM_ID || 'DKEbMD61G68RhqK37Z7Sxkf7NeuQ6WGm3q4PsA4j5kpK');
 
         this.program = new anchor.Program(ESCROW_IDL, programId, provider);
+        this.registry = new anchor.Program(
+            REGISTRY_IDL,
+            new PublicKey(process.env.REGISTRY_PROGRAM_ID || '9UZ8YFVGhZ5UBx9U8g7EzFjFCyP4P1g15UBdEpT82P1M'),
+            provider
+        );
         this.provider = provider;
         this.wallet = wallet;
 
@@ -144,6 +213,11 @@ class TaskVerifier {
             [Buffer.from('oracle_config')],
             programId
         );
+        // Signs the registry CPIs that record poster stats and agent reputation
+        [this.escrowAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from('escrow_authority')],
+            programId
+        );
 
         console.log('🔮 Oracle wallet:', wallet.publicKey.toBase58());
         console.log('📋 Escrow program:', programId.toBase58());
@@ -161,7 +235,7 @@ class TaskVerifier {
     }
 
     async processResult(resultAccount) {
-        const { taskEscrow, worker, resultUrl, urlHash, submittedAt } = resultAccount.account;
+        const { taskEscrow, worker, resultUrl, urlHash, submittedAt, commitment, contentDigest, encryption } = resultAccount.account;
         const resultPda = resultAccount.publicKey;
 
         console.log(`\n🔍 Processing result for task: ${taskEscrow.toBase58().slice(0, 8)}...`);
@@ -175,33 +249,123 @@ class TaskVerifier {
             return;
         }
 
-        let passed = false;
+        // UrlHash only commits to the URL (checked on-chain at submit); UrlAndDigest
+        // binds the artifact digest the worker reveals before the verdict.
+        const scheme = Object.keys(commitment)[0];
+        if (scheme === 'urlAndDigest' && !contentDigest) {
+            console.log('⏳ Content digest not revealed yet — skipping');
+            return;
+        }
+        const expected = scheme === 'urlAndDigest' ? contentDigest : urlHash;
+
+        let contentKey = null;
+        if (encryption) {
+            contentKey = loadContentKey(resultPda);
+            if (!contentKey) {
+                console.log('⏳ Content key not handed to the oracle yet — skipping');
+                return;
+            }
+        }
+
+        // Judge only against the spec the poster committed to. A spec that changed or
+        // vanished is not the worker's fault, so the result is left for reclaim instead.
+        let task, specHash;
         try {
-            const body = await fetchWithRetry(resultUrl, MAX_RETRIES, FETCH_TIMEOUT_MS);
+            task = await this.program.account.taskEscrow.fetch(taskEscrow);
+            const spec = await fetchWithRetry(task.specUri, MAX_RETRIES, FETCH_TIMEOUT_MS);
+            specHash = sha256(spec);
+        } catch (e) {
+            console.log(`⚠️ Spec fetch failed (${e.message}) — not judging`);
+            return;
+        }
+        if (!specHash.equals(Buffer.from(task.specHash))) {
+            console.log('❌ Spec at spec_uri does not match the on-chain spec_hash — not judging');
+            return;
+        }
+
+        let reason = 'accepted';
+        let plaintextHash = null;
+        try {
+            let body = await fetchWithRetry(resultUrl, MAX_RETRIES, FETCH_TIMEOUT_MS);
 
             // Verify content hash matches on-chain commitment
-            if (!hashesMatch(body, urlHash)) {
+            if (scheme !== 'urlHash' && !hashesMatch(body, expected)) {
                 console.log('❌ Hash mismatch — content was modified after submission');
-                passed = false;
+                reason = 'hashMismatch';
             } else {
-                passed = evaluateOutput(body);
-                console.log(`📊 Evaluation: ${passed ? 'PASSED' : 'FAILED'}`);
+                if (contentKey) {
+                    try {
+                        body = decryptDeliverable(body, contentKey);
+                        plaintextHash = sha256(body).toString('hex');
+                    } catch {
+                        // A key that does not open the ciphertext leaves nothing to judge
+                        console.log('❌ Content key does not decrypt the deliverable');
+                        body = null;
+                    }
+                }
+                if (!body) {
+                    reason = 'fetchFailed';
+                } else if (!evaluateOutput(body)) {
+                    reason = 'incomplete';
+                } else if (resultAccount.account.git && !(await checkGitDeliverable(resultAccount.account.git))) {
+                    reason = 'gitMismatch';
+                }
+                console.log(`📊 Evaluation: ${reason === 'accepted' ? 'PASSED' : 'FAILED'}`);
             }
         } catch (e) {
             console.log(`⚠️ Fetch failed (${e.message}) — marking as failed`);
-            passed = false;
+            reason = 'fetchFailed';
         }
+        const passed = reason === 'accepted';
+        // evaluateOutput is pass/fail, so scores and payouts are all-or-nothing until
+        // graded eval lands; the program accepts any payoutBps with a non-Accepted reason.
+        const score = passed ? 100 : 0;
+        const payoutBps = passed ? 10000 : 0;
+
+        const report = JSON.stringify({
+            taskEscrow: taskEscrow.toBase58(),
+            result: resultPda.toBase58(),
+            worker: worker.toBase58(),
+            resultUrl,
+            commitment: scheme,
+            plaintextHash,
+            specHash: specHash.toString('hex'),
+            reason,
+            score,
+            payoutBps,
+            evaluatedAt: now,
+        });
+        fs.mkdirSync(REPORT_DIR, { recursive: true });
+        fs.writeFileSync(path.join(REPORT_DIR, `${resultPda.toBase58()}.json`), report);
+        const verdict = { passed, score, payoutBps, reason: { [reason]: {} }, reportHash: [...sha256(report)] };
 
         try {
+            const taskBoard = task.listed ? this.boardPda(task.boardTier, task.boardPage) : null;
+            const [posterProfile] = PublicKey.findProgramAddressSync(
+                [Buffer.from('poster'), task.poster.toBuffer()],
+                this.registry.programId
+            );
+            const reputation = await this.reputationAccounts(worker);
+
             await this.program.methods
-                .verifyResult(passed)
+                .verifyResult(verdict, [...specHash])
                 .accounts({
                     oracle: this.wallet.publicKey,
                     oracleConfig: this.oracleConfigPda,
                     taskEscrow: taskEscrow,
                     taskResult: resultPda,
                     worker: worker,
+                    poster: task.poster,
+                    parentTask: task.parent,
+                    taskBoard,
+                    ...(await this.parentBoards(task, payoutBps)),
+                    posterProfile,
+                    escrowAuthority: this.escrowAuthority,
+                    registryProgram: this.registry.programId,
+                    ...reputation,
                 })
+                // Team tasks split the bounty across every member wallet
+                .remainingAccounts(task.team.map(m => ({ pubkey: m.agent, isWritable: true, isSigner: false })))
                 .rpc();
 
             console.log(`✅ Verdict submitted on-chain: ${passed ? 'PASS — bounty released' : 'FAIL — task reopened'}`);
@@ -210,6 +374,67 @@ class TaskVerifier {
         }
     }
 
+    boardPda(tier, page) {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('board'), Buffer.from([tier]), new anchor.BN(page).toArrayLike(Buffer, 'le', 4)],
+            this.program.programId
+        )[0];
+    }
+
+    // First page of `tier` with a free slot. Pages are permissionless to create, so
+    // when every existing page is full the oracle opens the next one itself.
+    async freeBoardPage(tier) {
+        for (let page = 0; ; page++) {
+            const pda = this.boardPda(tier, page);
+            const board = await this.program.account.taskBoard.fetchNullable(pda);
+            if (board && board.count >= BOARD_PAGE_SIZE) continue;
+            if (!board) {
+                await this.program.methods
+                    .initializeBoardPage(tier, page)
+                    .accounts({ taskBoard: pda, payer: this.wallet.publicKey })
+                    .rpc();
+                console.log(`📄 Opened board page ${page} of tier ${tier}`);
+            }
+            return pda;
+        }
+    }
+
+    // A subtask's unpaid share flows back into its parent, which can move the parent's
+    // board entry up a tier onto a page of the new tier with room for it.
+    async parentBoards(task, payoutBps) {
+        if (!task.parent) return {};
+        const parent = await this.program.account.taskEscrow.fetch(task.parent);
+        if (!parent.listed) return {};
+
+        const bounty = BigInt(task.bountyAmount.toString());
+        const refund = bounty - (bounty * BigInt(payoutBps)) / 10000n;
+        const parentBounty = BigInt(parent.bountyAmount.toString()) + refund;
+        const tier = BOUNTY_TIER_LIMITS.filter(limit => parentBounty >= limit).length;
+        return {
+            parentBoard: this.boardPda(parent.boardTier, parent.boardPage),
+            parentTierBoard: tier === parent.boardTier ? null : await this.freeBoardPage(tier),
+        };
+    }
+
+    // Registry accounts that let the verdict score feed the worker's reputation;
+    // workers without an agent profile are judged without a reputation update.
+    async reputationAccounts(worker) {
+        const [agentAccount] = PublicKey.findProgramAddressSync(
+            [Buffer.from('agent'), worker.toBuffer()],
+            this.registry.programId
+        );
+        const agent = await this.registry.account.agentAccount.fetchNullable(agentAccount);
+        if (!agent) return {};
+
+        const agentDirectory = agent.listed
+            ? PublicKey.findProgramAddressSync(
+                [Buffer.from('directory'), new anchor.BN(agent.directoryPage).toArrayLike(Buffer, 'le', 4)],
+                this.registry.programId
+            )[0]
+            : null;
+        return { agentAccount, agentDirectory };
+    }
+
     async runLoop() {
         console.log('\n🔄 Scanning for pending task results...');
         const pending = await this.getPendingResults();
@@ -1,27 +1,73 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::Discriminator;
 use anchor_lang::system_program::{transfer, Transfer};
+use registry::program::Registry;
+use registry::{AgentAccount, OperatorKey, PosterEvent, SessionKey, PERM_PLACE_BIDS, PERM_SUBMIT_RESULTS};
 
 declare_id!("DKEbMD61G68RhqK37Z7Sxkf7NeuQ6WGm3q4PsA4j5kpK");
 
+/// How long the oracle has to settle before workers can reclaim (48 hours)
+pub const ORACLE_TIMEOUT_SECS: i64 = 48 * 3600;
+/// How long a worker has to publish the key of a passed encrypted result (24 hours)
+pub const KEY_RELEASE_TIMEOUT_SECS: i64 = 24 * 3600;
+/// Largest key blob `release_key` accepts (a key sealed to the poster fits easily)
+pub const MAX_KEY_BLOB_LEN: usize = 128;
+/// Longest repository identifier a git deliverable may name, e.g. "github.com/org/repo"
+pub const MAX_REPOSITORY_LEN: usize = 100;
+/// Maximum number of paid ranks in a contest
+pub const MAX_CONTEST_WINNERS: usize = 5;
+/// Maximum number of agents sharing a team task
+pub const MAX_TEAM_SIZE: usize = 5;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Most of a task's bounty its assigned agent may carve into subtasks (50%)
+pub const MAX_DELEGATED_BPS: u64 = 5_000;
+/// How long an assigned agent has to submit before the poster can reclaim the task (7 days)
+pub const ABANDON_TIMEOUT_SECS: i64 = 7 * 24 * 3600;
+/// How long a subtask may stay unsettled before the parent's poster can cancel it (7 days)
+pub const SUBTASK_STALE_SECS: i64 = 7 * 24 * 3600;
+/// Current `TaskEscrow` layout. Accounts created before versioning are migrated with `migrate_task`.
+pub const TASK_ESCROW_VERSION: u8 = 1;
+/// Tasks per `TaskBoard` page (56 bytes each)
+pub const BOARD_PAGE_SIZE: usize = 128;
+/// Upper bounds (exclusive) of the bounty tiers boards are sharded by: <0.1, <1, <10 and >=10 SOL
+pub const BOUNTY_TIER_LIMITS: [u64; 3] = [100_000_000, 1_000_000_000, 10_000_000_000];
 
 #[program]
 pub mod escrow {
     use super::*;
 
-    // 1. Initialize Task: Poster deposits SOL into the TaskEscrow PDA
+    // 1. Initialize Task: Poster deposits SOL into the TaskEscrow PDA and lists it on the
+    // board page for its bounty tier, tagged with the skills an agent needs.
+    // The PDA is seeded by the poster's next task nonce, not `task_id`; the human-readable
+    // ID is only stored, so it may be any string up to 50 bytes and may repeat.
+    // The full spec lives off-chain at `spec_uri`; `spec_hash` commits to its contents
+    // the same way `TaskResult.url_hash` commits to a result.
     pub fn initialize_task(
         ctx: Context<InitializeTask>, 
         task_id: String, 
         bounty_amount: u64,
-        description: String
+        title: String,
+        spec_uri: String,
+        spec_hash: [u8; 32],
+        required_skills: u64,
     ) -> Result<()> {
+        require!(task_id.len() <= 50, EscrowError::TaskIdTooLong);
+        require!(title.len() <= 64, EscrowError::TitleTooLong);
+        require!(spec_uri.len() <= 256, EscrowError::UrlTooLong);
+
         let task_escrow = &mut ctx.accounts.task_escrow;
         task_escrow.poster = ctx.accounts.poster.key();
         task_escrow.task_id = task_id;
-        task_escrow.description = description;
+        task_escrow.title = title;
+        task_escrow.spec_uri = spec_uri;
+        task_escrow.spec_hash = spec_hash;
         task_escrow.bounty_amount = bounty_amount;
+        task_escrow.total_funded = bounty_amount;
         task_escrow.status = TaskStatus::Open;
         task_escrow.created_at = Clock::get()?.unix_timestamp;
+        task_escrow.nonce = take_task_nonce(&mut ctx.accounts.poster_nonce, ctx.accounts.poster.key(), ctx.bumps.poster_nonce)?;
+        task_escrow.version = TASK_ESCROW_VERSION;
         task_escrow.bump = ctx.bumps.task_escrow;
 
         // Transfer SOL from poster to the escrow PDA
@@ -31,6 +77,14 @@ pub mod escrow {
         };
         let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
         transfer(cpi_ctx, bounty_amount)?;
+        list_on_board(&ctx.accounts.task_board, task_escrow, required_skills)?;
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskPosted,
+        )?;
 
         msg!("Task initialized: {} with bounty {} lamports", task_escrow.task_id, bounty_amount);
         Ok(())
@@ -42,15 +96,33 @@ pub mod escrow {
     pub fn assign_agent(ctx: Context<AssignAgent>, agent: Pubkey) -> Result<()> {
         let task_escrow = &mut ctx.accounts.task_escrow;
         require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(task_escrow.contest.is_none(), EscrowError::ContestTask);
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+
+        // Bonded tasks: the agent co-signs and locks the bond in the task until they submit
+        if task_escrow.worker_bond > 0 {
+            let agent_signer = ctx.accounts.agent.as_ref().ok_or(EscrowError::AgentConsentRequired)?;
+            require!(agent_signer.key() == agent, EscrowError::WrongAgent);
+            let cpi_accounts = Transfer {
+                from: agent_signer.to_account_info(),
+                to: task_escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, task_escrow.worker_bond)?;
+            task_escrow.assignment_bond = task_escrow.worker_bond;
+        }
 
         task_escrow.assigned_agent = Some(agent);
         task_escrow.status = TaskStatus::InProgress;
+        task_escrow.started_at = Some(Clock::get()?.unix_timestamp);
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
         
         msg!("Task assigned to agent: {}", agent);
         Ok(())
     }
 
     // 3. Complete Task: Poster verifies work and releases funds to the Agent
+    // Team tasks pass the member wallets as remaining_accounts, in assignment order.
     pub fn complete_task(ctx: Context<CompleteTask>) -> Result<()> {
         let task_escrow = &mut ctx.accounts.task_escrow;
         
@@ -66,25 +138,119 @@ pub mod escrow {
             require!(assigned == dest_agent.key(), EscrowError::WrongAgent);
         }
 
-        // Payout: Transfer SOL from PDA to Agent
-        // Since PDA "owns" the lamports, we can decrease its balance and increase agent's
-        **task_escrow.to_account_info().try_borrow_mut_lamports()? -= task_escrow.bounty_amount;
-        **dest_agent.to_account_info().try_borrow_mut_lamports()? += task_escrow.bounty_amount;
+        require!(task_escrow.contest.is_none(), EscrowError::ContestTask);
+        require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
+        // A subtask's poster is the delegating agent, who must not pay out parent funds alone
+        require!(task_escrow.parent.is_none(), EscrowError::SubtaskNeedsOracle);
 
+        // Payout: Transfer SOL from PDA to Agent (or split across the team)
+        release_bounty(
+            &task_escrow.to_account_info(),
+            &task_escrow.team,
+            dest_agent,
+            ctx.remaining_accounts,
+            task_escrow.bounty_amount,
+        )?;
+        // The poster accepted the work directly, so the assignment bond goes back too
+        if task_escrow.assignment_bond > 0 {
+            pay_from_escrow(&task_escrow.to_account_info(), dest_agent, task_escrow.assignment_bond)?;
+            task_escrow.assignment_bond = 0;
+        }
+        // So does the bond of a result still awaiting its verdict, which this payout settles
+        if let Some(result) = load_result(&ctx.accounts.task_result)?.filter(is_unsettled) {
+            require!(result.worker == dest_agent.key(), EscrowError::WrongAgent);
+            close_result(&ctx.accounts.task_result, dest_agent)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
         task_escrow.status = TaskStatus::Completed;
         task_escrow.completed_agent = Some(dest_agent.key());
-        task_escrow.completed_at = Some(Clock::get()?.unix_timestamp);
+        task_escrow.completed_at = Some(now);
+        release_parent(task_escrow, &mut ctx.accounts.parent_task, 0, &None, &None)?;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskPaid {
+                amount: task_escrow.bounty_amount,
+                verify_secs: task_escrow.started_at.map(|started| (now - started) as u64),
+            },
+        )?;
 
         msg!("Task completed! Funds released to {}", dest_agent.key());
         Ok(())
     }
 
     // 4. Cancel Task: Poster cancels and gets refund
+    // With outside contributors the bounty is refunded pro-rata: the poster's share now,
+    // each contributor's via claim_refund. The account closes after the last claim.
     pub fn cancel_task(ctx: Context<CancelTask>) -> Result<()> {
         let task_escrow = &mut ctx.accounts.task_escrow;
 
         require!(task_escrow.poster == ctx.accounts.poster.key(), EscrowError::Unauthorized);
         require!(task_escrow.status != TaskStatus::Completed, EscrowError::TaskAlreadyCompleted);
+        require!(task_escrow.status != TaskStatus::Cancelled, EscrowError::InvalidStatus);
+        // A live round holds bidders' bonds against this bounty; award it first
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+        // Cancelling would take a submitted result unpaid and strand the worker's bond
+        require!(
+            load_result(&ctx.accounts.task_result)?.filter(is_unsettled).is_none(),
+            EscrowError::ResultPending
+        );
+
+        // Once a contest has entries the poster can't walk away with the bounty
+        // unless settlement has stalled past the oracle timeout.
+        if let Some(contest) = &task_escrow.contest {
+            let now = Clock::get()?.unix_timestamp;
+            require!(
+                contest.entry_count == 0 || now > contest.submission_deadline + ORACLE_TIMEOUT_SECS,
+                EscrowError::ContestHasEntries
+            );
+        }
+        require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
+
+        // A subtask's bounty belongs to its parent; only the rent goes back to the poster
+        let refund = task_escrow.bounty_amount;
+        release_parent(
+            task_escrow,
+            &mut ctx.accounts.parent_task,
+            refund,
+            &ctx.accounts.parent_board,
+            &ctx.accounts.parent_tier_board,
+        )?;
+
+        // Cancelling isn't the agent's doing, so their assignment bond is returned
+        if task_escrow.assignment_bond > 0 {
+            let agent = ctx.accounts.agent.as_ref().ok_or(EscrowError::WrongAgent)?;
+            pay_from_escrow(&task_escrow.to_account_info(), agent, task_escrow.assignment_bond)?;
+            task_escrow.assignment_bond = 0;
+        }
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskCancelled {
+                after_start: task_escrow.started_at.is_some(),
+            },
+        )?;
+
+        task_escrow.status = TaskStatus::Cancelled;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        let poster = ctx.accounts.poster.to_account_info();
+        if task_escrow.contributor_count == 0 {
+            task_escrow.close(poster)?;
+        } else {
+            let poster_funded = task_escrow
+                .total_funded
+                .checked_sub(task_escrow.contributed)
+                .ok_or(EscrowError::Overflow)?;
+            let poster_share = pro_rata(task_escrow.bounty_amount, poster_funded, task_escrow.total_funded)?;
+            pay_from_escrow(&task_escrow.to_account_info(), &poster, poster_share)?;
+        }
 
         msg!("Task cancelled. Funds refunded.");
         Ok(())
@@ -112,9 +278,18 @@ pub mod escrow {
             task_escrow.status == TaskStatus::Open || task_escrow.status == TaskStatus::InProgress,
             EscrowError::InvalidStatus
         );
+        require!(task_escrow.contest.is_none(), EscrowError::ContestTask);
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+
+        // An operator or session key submits on behalf of its agent, who is credited and paid
+        let worker = resolve_agent(
+            ctx.accounts.worker.key(),
+            &ctx.accounts.operator_key,
+            &ctx.accounts.session_key,
+            PERM_SUBMIT_RESULTS,
+        )?;
 
         // If task has an assigned agent, only that agent can submit
-        let worker = ctx.accounts.worker.key();
         if let Some(assigned) = task_escrow.assigned_agent {
             require!(assigned == worker, EscrowError::WrongAgent);
         }
@@ -129,252 +304,2794 @@ pub mod escrow {
         result.oracle_nonce = 0;
         result.bump = ctx.bumps.task_result;
 
+        // Bonded tasks: the worker locks the bond in the result PDA until the verdict. A bond
+        // posted at assignment moves over from the task; otherwise the worker posts it now.
+        if task_escrow.assignment_bond > 0 {
+            pay_from_escrow(&task_escrow.to_account_info(), &result.to_account_info(), task_escrow.assignment_bond)?;
+            result.bond = task_escrow.assignment_bond;
+            task_escrow.assignment_bond = 0;
+        } else if task_escrow.worker_bond > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.worker.to_account_info(),
+                to: result.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, task_escrow.worker_bond)?;
+            result.bond = task_escrow.worker_bond;
+
+            if let Some(session_key) = &ctx.accounts.session_key {
+                record_session_spend(
+                    session_key,
+                    &ctx.accounts.escrow_authority,
+                    &ctx.accounts.registry_program,
+                    task_escrow.worker_bond,
+                )?;
+            }
+        }
+
         task_escrow.status = TaskStatus::InProgress;
         task_escrow.assigned_agent = Some(worker);
+        task_escrow.started_at.get_or_insert(result.submitted_at);
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
 
         msg!("Result submitted by {}. URL: {}", worker, result_url);
         Ok(())
     }
 
     // 7. Verify Result: Oracle evaluates and releases escrow or marks failed
-    pub fn verify_result(
-        ctx: Context<VerifyResult>,
-        passed: bool,
+    // `spec_hash` is the hash of the spec the oracle evaluated against; the verdict
+    // is rejected if it doesn't match the one the poster committed to.
+    pub fn verify_result<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyResult<'info>>,
+        verdict: Verdict,
+        spec_hash: [u8; 32],
     ) -> Result<()> {
         // Only the registered oracle can call this
         require!(
             ctx.accounts.oracle.key() == ctx.accounts.oracle_config.oracle,
             EscrowError::Unauthorized
         );
+        require!(
+            spec_hash == ctx.accounts.task_escrow.spec_hash,
+            EscrowError::SpecHashMismatch
+        );
+        // A pass pays some share of the bounty; anything short of all of it names a reason
+        require!(
+            verdict.score <= 100
+                && verdict.payout_bps as u64 <= BPS_DENOMINATOR
+                && verdict.passed == (verdict.payout_bps > 0)
+                && (verdict.reason == VerdictReason::Accepted) == (verdict.payout_bps as u64 == BPS_DENOMINATOR),
+            EscrowError::InvalidVerdict
+        );
 
+        require!(ctx.accounts.task_escrow.status == TaskStatus::InProgress, EscrowError::InvalidStatus);
         let result = &mut ctx.accounts.task_result;
         require!(result.status == ResultStatus::Pending, EscrowError::AlreadySettled);
+        // A digest commitment must be opened so the verdict names the exact artifact judged
+        require!(
+            result.commitment != CommitmentScheme::UrlAndDigest || result.content_digest.is_some(),
+            EscrowError::DigestNotRevealed
+        );
 
         let task_escrow = &mut ctx.accounts.task_escrow;
+        let passed = verdict.passed;
 
         if passed {
-            // Release bounty to worker
-            **task_escrow.to_account_info().try_borrow_mut_lamports()? -= task_escrow.bounty_amount;
-            **ctx.accounts.worker.to_account_info().try_borrow_mut_lamports()? += task_escrow.bounty_amount;
-
-            task_escrow.status = TaskStatus::Completed;
-            task_escrow.completed_agent = Some(result.worker);
-            task_escrow.completed_at = Some(Clock::get()?.unix_timestamp);
-            result.status = ResultStatus::Verified;
-
-            msg!("Result verified. Bounty released to {}", result.worker);
+            if let Some(encryption) = result.encryption.as_mut().filter(|e| e.released_key.is_empty()) {
+                // Encrypted delivery: payment waits for the worker to publish the key
+                encryption.verified_at = Some(Clock::get()?.unix_timestamp);
+                result.status = ResultStatus::AwaitingKey;
+                msg!("Encrypted result verified. Awaiting key release from {}", result.worker);
+            } else {
+                pay_passed_result(
+                    task_escrow,
+                    result,
+                    verdict.payout_bps,
+                    &ctx.accounts.worker,
+                    &ctx.accounts.poster,
+                    ctx.remaining_accounts,
+                    &mut ctx.accounts.parent_task,
+                    &ctx.accounts.parent_board,
+                    &ctx.accounts.parent_tier_board,
+                    &ctx.accounts.poster_profile,
+                    &ctx.accounts.escrow_authority,
+                    &ctx.accounts.registry_program,
+                )?;
+                msg!("Result verified. {} bps of the bounty released to {}", verdict.payout_bps, result.worker);
+            }
         } else {
-            result.status = ResultStatus::Failed;
+            // Bond is slashed to the poster; the result closes (rent to the worker) so
+            // another worker can submit
+            pay_from_escrow(&result.to_account_info(), &ctx.accounts.poster, result.bond)?;
+            result.close(ctx.accounts.worker.to_account_info())?;
+
+            task_escrow.assigned_agent = None;
+            task_escrow.team.clear();
+            task_escrow.started_at = None;
             task_escrow.status = TaskStatus::Open; // Re-open for another worker
-            msg!("Result failed Oracle verification.");
+            msg!("Result failed Oracle verification: {:?}", verdict.reason);
         }
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        record_verdict(
+            &ctx.accounts.agent_account,
+            &ctx.accounts.agent_directory,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            &verdict,
+        )?;
+        result.verdict = Some(verdict);
 
         Ok(())
     }
 
     // 8. Reclaim Escrow: Worker reclaims if Oracle doesn't respond within timeout
     pub fn reclaim_timeout(ctx: Context<ReclaimTimeout>) -> Result<()> {
-        let result = &ctx.accounts.task_result;
+        let result = &mut ctx.accounts.task_result;
         let task_escrow = &mut ctx.accounts.task_escrow;
 
         require!(result.status == ResultStatus::Pending, EscrowError::AlreadySettled);
+        require!(task_escrow.status == TaskStatus::InProgress, EscrowError::InvalidStatus);
+        // An unverified encrypted result is only paid once the poster can open it
+        if let Some(encryption) = &result.encryption {
+            require!(!encryption.released_key.is_empty(), EscrowError::KeyNotReleased);
+        }
+        // Likewise a digest commitment must be opened to name the artifact being paid for
+        require!(
+            result.commitment != CommitmentScheme::UrlAndDigest || result.content_digest.is_some(),
+            EscrowError::DigestNotRevealed
+        );
 
         // 48 hour timeout
         let now = Clock::get()?.unix_timestamp;
-        require!(now - result.submitted_at > 48 * 3600, EscrowError::TimeoutNotReached);
+        require!(now - result.submitted_at > ORACLE_TIMEOUT_SECS, EscrowError::TimeoutNotReached);
 
         require!(result.worker == ctx.accounts.worker.key(), EscrowError::Unauthorized);
+        require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
 
-        // Release bounty to worker after timeout
-        **task_escrow.to_account_info().try_borrow_mut_lamports()? -= task_escrow.bounty_amount;
-        **ctx.accounts.worker.to_account_info().try_borrow_mut_lamports()? += task_escrow.bounty_amount;
+        // Release bounty to worker (or split across the team) after timeout
+        release_bounty(
+            &task_escrow.to_account_info(),
+            &task_escrow.team,
+            &ctx.accounts.worker.to_account_info(),
+            ctx.remaining_accounts,
+            task_escrow.bounty_amount,
+        )?;
 
         task_escrow.status = TaskStatus::Completed;
         task_escrow.completed_agent = Some(result.worker);
         task_escrow.completed_at = Some(now);
+        release_parent(task_escrow, &mut ctx.accounts.parent_task, 0, &None, &None)?;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        // The oracle never ruled against the worker, so the bond is returned
+        pay_from_escrow(&result.to_account_info(), &ctx.accounts.worker.to_account_info(), result.bond)?;
+        result.status = ResultStatus::Expired;
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskPaid {
+                amount: task_escrow.bounty_amount,
+                verify_secs: None,
+            },
+        )?;
 
         msg!("Escrow reclaimed by worker after Oracle timeout.");
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(task_id: String)]
-pub struct InitializeTask<'info> {
-    #[account(mut)]
-    pub poster: Signer<'info>,
+    // 9. Open Contest: Poster turns an open task into a bounty many workers can compete for.
+    // `payout_bps[i]` is the share paid to rank i; shares must add up to 100%.
+    pub fn open_contest(
+        ctx: Context<UpdateTask>,
+        submission_deadline: i64,
+        payout_bps: Vec<u16>,
+    ) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(
+            task_escrow.assigned_agent.is_none() && task_escrow.contest.is_none(),
+            EscrowError::InvalidStatus
+        );
+        require!(task_escrow.parent.is_none(), EscrowError::ContestTask);
+        require!(
+            submission_deadline > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidDeadline
+        );
+        require!(
+            !payout_bps.is_empty() && payout_bps.len() <= MAX_CONTEST_WINNERS,
+            EscrowError::InvalidPayoutSplit
+        );
+        let total: u64 = payout_bps.iter().map(|bps| *bps as u64).sum();
+        require!(total == BPS_DENOMINATOR, EscrowError::InvalidPayoutSplit);
 
-    #[account(
-        init,
-        payer = poster,
-        space = 8 + TaskEscrow::INIT_SPACE,
-        seeds = [b"task", poster.key().as_ref(), task_id.as_bytes()],
-        bump
-    )]
-    pub task_escrow: Account<'info, TaskEscrow>,
+        task_escrow.contest = Some(ContestConfig {
+            submission_deadline,
+            payout_bps,
+            entry_count: 0,
+        });
 
-    pub system_program: Program<'info, System>,
-}
+        msg!("Contest opened for {} until {}", task_escrow.task_id, submission_deadline);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeOracle<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + OracleConfig::INIT_SPACE,
-        seeds = [b"oracle_config"],
-        bump
-    )]
-    pub oracle_config: Account<'info, OracleConfig>,
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    // 10. Submit Entry: Worker enters a contest. One entry per worker per task.
+    pub fn submit_entry(
+        ctx: Context<SubmitEntry>,
+        result_url: String,
+        url_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(result_url.len() <= 256, EscrowError::UrlTooLong);
 
-#[derive(Accounts)]
-#[instruction(result_url: String)]
-pub struct SubmitResult<'info> {
-    #[account(mut)]
-    pub worker: Signer<'info>,
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        let now = Clock::get()?.unix_timestamp;
+        let contest = task_escrow.contest.as_mut().ok_or(EscrowError::NotAContest)?;
+        require!(now < contest.submission_deadline, EscrowError::SubmissionWindowClosed);
+        contest.entry_count = contest.entry_count.checked_add(1).ok_or(EscrowError::Overflow)?;
 
-    #[account(mut)]
-    pub task_escrow: Account<'info, TaskEscrow>,
+        let worker = ctx.accounts.worker.key();
+        let entry = &mut ctx.accounts.entry;
+        entry.task_escrow = task_escrow.key();
+        entry.worker = worker;
+        entry.result_url = result_url;
+        entry.url_hash = url_hash;
+        entry.status = ResultStatus::Pending;
+        entry.submitted_at = now;
+        entry.oracle_nonce = 0;
+        entry.bump = ctx.bumps.entry;
 
-    #[account(
-        init,
-        payer = worker,
-        space = 8 + TaskResult::INIT_SPACE,
-        seeds = [b"result", task_escrow.key().as_ref()],
-        bump
-    )]
-    pub task_result: Account<'info, TaskResult>,
+        msg!("Contest entry submitted by {}", worker);
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    // 11. Settle Contest: Oracle or poster ranks entries after the window closes.
+    // remaining_accounts holds (entry, worker wallet) pairs, best first. Every paid rank
+    // must be filled while entries remain; shares for ranks nobody entered for and
+    // rounding dust go back to the funders pro-rata.
+    pub fn settle_contest<'info>(ctx: Context<'_, '_, 'info, 'info, SettleContest<'info>>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        let is_oracle = ctx
+            .accounts
+            .oracle_config
+            .as_ref()
+            .is_some_and(|config| config.oracle == authority);
+        require!(authority == task_escrow.poster || is_oracle, EscrowError::Unauthorized);
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
 
-#[derive(Accounts)]
-pub struct VerifyResult<'info> {
-    pub oracle: Signer<'info>,
+        let now = Clock::get()?.unix_timestamp;
+        let contest = task_escrow.contest.clone().ok_or(EscrowError::NotAContest)?;
+        require!(now >= contest.submission_deadline, EscrowError::SubmissionWindowOpen);
 
-    #[account(seeds = [b"oracle_config"], bump)]
-    pub oracle_config: Account<'info, OracleConfig>,
+        let ranked = ctx.remaining_accounts;
+        let paid_ranks = contest.payout_bps.len().min(contest.entry_count as usize);
+        require!(ranked.len() == paid_ranks * 2, EscrowError::InvalidRanking);
 
-    #[account(mut)]
-    pub task_escrow: Account<'info, TaskEscrow>,
+        let escrow_info = task_escrow.to_account_info();
+        let task_key = task_escrow.key();
+        let bounty = task_escrow.bounty_amount;
+        let mut paid: u64 = 0;
+        let mut winners: Vec<Pubkey> = Vec::with_capacity(ranked.len() / 2);
 
-    #[account(
-        mut,
-        seeds = [b"result", task_escrow.key().as_ref()],
-        bump = task_result.bump
-    )]
-    pub task_result: Account<'info, TaskResult>,
+        for (rank, pair) in ranked.chunks(2).enumerate() {
+            let (entry_info, worker_info) = (&pair[0], &pair[1]);
+            let mut entry: Account<TaskResult> = Account::try_from(entry_info)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"entry", task_key.as_ref(), entry.worker.as_ref()],
+                ctx.program_id,
+            );
+            require!(entry_info.key() == expected, EscrowError::InvalidRanking);
+            require!(entry.status == ResultStatus::Pending, EscrowError::AlreadySettled);
+            require!(worker_info.key() == entry.worker, EscrowError::WrongAgent);
+            require!(!winners.contains(&entry.worker), EscrowError::InvalidRanking);
 
-    /// CHECK: Worker wallet to receive bounty on pass
-    #[account(mut, constraint = worker.key() == task_result.worker @ EscrowError::WrongAgent)]
-    pub worker: AccountInfo<'info>,
-}
+            let share = pro_rata(bounty, contest.payout_bps[rank] as u64, BPS_DENOMINATOR)?;
+            pay_from_escrow(&escrow_info, worker_info, share)?;
+            paid += share;
 
-#[derive(Accounts)]
-pub struct ReclaimTimeout<'info> {
-    #[account(mut)]
-    pub worker: Signer<'info>,
+            entry.status = ResultStatus::Verified;
+            entry.exit(ctx.program_id)?;
+            winners.push(entry.worker);
+        }
 
-    #[account(mut)]
-    pub task_escrow: Account<'info, TaskEscrow>,
+        // Shares of empty ranks go back to the funders pro-rata (backers claim theirs)
+        let leftover = bounty - paid;
+        if leftover > 0 {
+            refund_funders(task_escrow, &ctx.accounts.poster, leftover)?;
+        }
 
-    #[account(
-        mut,
-        seeds = [b"result", task_escrow.key().as_ref()],
-        bump = task_result.bump,
-        constraint = task_result.worker == worker.key() @ EscrowError::Unauthorized
-    )]
-    pub task_result: Account<'info, TaskResult>,
-}
+        task_escrow.status = TaskStatus::Completed;
+        task_escrow.completed_agent = winners.first().copied();
+        task_escrow.completed_at = Some(now);
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
 
-#[derive(Accounts)]
-pub struct AssignAgent<'info> {
-    #[account(mut)]
-    pub poster: Signer<'info>,
-    #[account(
-        mut,
-        has_one = poster @ EscrowError::Unauthorized
-    )]
-    pub task_escrow: Account<'info, TaskEscrow>,
-}
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskPaid {
+                amount: paid,
+                verify_secs: None,
+            },
+        )?;
 
-#[derive(Accounts)]
-pub struct CompleteTask<'info> {
-    #[account(mut)]
-    pub poster: Signer<'info>,
-    
-    /// CHECK: The agent account to receive funds. Verified by logic if assigned_agent is set.
-    #[account(mut)]
-    pub agent: AccountInfo<'info>,
-    
-    #[account(
-        mut,
-        has_one = poster @ EscrowError::Unauthorized
-    )]
-    pub task_escrow: Account<'info, TaskEscrow>,
-}
+        msg!("Contest settled. {} winner(s), {} lamports refunded", winners.len(), leftover);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct CancelTask<'info> {
-    #[account(mut)]
-    pub poster: Signer<'info>,
-    
-    #[account(
-        mut,
-        close = poster, // This instruction moves all lamports (rent + bounty) back to poster
-        constraint = task_escrow.poster == poster.key() @ EscrowError::Unauthorized
-    )]
-    pub task_escrow: Account<'info, TaskEscrow>,
-}
+    // 12. Close Entry: Worker reclaims entry rent once the contest is over
+    pub fn close_entry(ctx: Context<CloseEntry>) -> Result<()> {
+        let task_info = &ctx.accounts.task_escrow;
+        // A cancelled task is closed outright; otherwise it must be settled
+        if !task_info.data_is_empty() {
+            require!(task_info.owner == ctx.program_id, EscrowError::InvalidStatus);
+            let task_escrow = TaskEscrow::try_deserialize(&mut &task_info.data.borrow()[..])?;
+            require!(task_escrow.status == TaskStatus::Completed, EscrowError::InvalidStatus);
+        }
 
-#[account]
-#[derive(InitSpace)]
-pub struct TaskEscrow {
-    pub poster: Pubkey,
-    #[max_len(50)]
-    pub task_id: String,
-    #[max_len(200)]
-    pub description: String,
-    pub bounty_amount: u64,
-    pub status: TaskStatus,
-    pub assigned_agent: Option<Pubkey>,
-    pub completed_agent: Option<Pubkey>,
-    pub created_at: i64,
-    pub completed_at: Option<i64>,
-    pub bump: u8,
-}
+        msg!("Contest entry closed. Rent returned to {}", ctx.accounts.worker.key());
+        Ok(())
+    }
 
-#[account]
-#[derive(InitSpace)]
-pub struct OracleConfig {
-    pub admin: Pubkey,
-    pub oracle: Pubkey,
-}
+    // 13. Assign Team: Poster assigns the task to several agents with fixed payout shares.
+    // The first member is the team lead: they submit results and receive rounding dust.
+    pub fn assign_team(ctx: Context<AssignAgent>, members: Vec<TeamMember>) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(task_escrow.contest.is_none(), EscrowError::ContestTask);
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+        require!(
+            members.len() >= 2 && members.len() <= MAX_TEAM_SIZE,
+            EscrowError::InvalidTeam
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(member.share_bps > 0, EscrowError::InvalidTeam);
+            require!(
+                !members[..i].iter().any(|m| m.agent == member.agent),
+                EscrowError::InvalidTeam
+            );
+        }
+        let total: u64 = members.iter().map(|m| m.share_bps as u64).sum();
+        require!(total == BPS_DENOMINATOR, EscrowError::InvalidPayoutSplit);
 
-#[account]
-#[derive(InitSpace)]
-pub struct TaskResult {
-    pub task_escrow: Pubkey,
-    pub worker: Pubkey,
-    #[max_len(256)]
-    pub result_url: String,
-    pub url_hash: [u8; 32],
-    pub status: ResultStatus,
-    pub submitted_at: i64,
-    pub oracle_nonce: u64,
-    pub bump: u8,
+        let lead = members[0].agent;
+        task_escrow.assigned_agent = Some(lead);
+        task_escrow.team = members;
+        task_escrow.status = TaskStatus::InProgress;
+        task_escrow.started_at = Some(Clock::get()?.unix_timestamp);
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        msg!("Task assigned to team of {} led by {}", task_escrow.team.len(), lead);
+        Ok(())
+    }
+
+    // 14. Delegate Subtask: The assigned agent carves part of their bounty into a child task
+    // they post themselves. The parent can't be completed until every child is settled or
+    // cancelled; a cancelled child's bounty flows back into the parent. Children are paid
+    // only through the oracle, and at most MAX_DELEGATED_BPS of the bounty can be carved out.
+    #[allow(clippy::too_many_arguments)]
+    pub fn delegate_subtask(
+        ctx: Context<DelegateSubtask>,
+        task_id: String,
+        amount: u64,
+        title: String,
+        spec_uri: String,
+        spec_hash: [u8; 32],
+        required_skills: u64,
+    ) -> Result<()> {
+        require!(task_id.len() <= 50, EscrowError::TaskIdTooLong);
+        require!(title.len() <= 64, EscrowError::TitleTooLong);
+        require!(spec_uri.len() <= 256, EscrowError::UrlTooLong);
+
+        let parent = &mut ctx.accounts.parent_task;
+        require!(parent.status == TaskStatus::InProgress, EscrowError::InvalidStatus);
+        require!(amount > 0 && amount <= parent.bounty_amount, EscrowError::InvalidAmount);
+        let delegated = parent.delegated.checked_add(amount).ok_or(EscrowError::Overflow)?;
+        let cap = (parent.bounty_amount as u128 + parent.delegated as u128) * MAX_DELEGATED_BPS as u128
+            / BPS_DENOMINATOR as u128;
+        require!(delegated as u128 <= cap, EscrowError::DelegationCapExceeded);
+
+        let child = &mut ctx.accounts.task_escrow;
+        pay_from_escrow(&parent.to_account_info(), &child.to_account_info(), amount)?;
+        parent.bounty_amount -= amount;
+        parent.delegated = delegated;
+        parent.open_subtasks = parent.open_subtasks.checked_add(1).ok_or(EscrowError::Overflow)?;
+
+        child.poster = ctx.accounts.agent.key();
+        child.task_id = task_id;
+        child.title = title;
+        child.spec_uri = spec_uri;
+        child.spec_hash = spec_hash;
+        child.bounty_amount = amount;
+        child.total_funded = amount;
+        child.status = TaskStatus::Open;
+        child.created_at = Clock::get()?.unix_timestamp;
+        child.nonce = take_task_nonce(&mut ctx.accounts.poster_nonce, ctx.accounts.agent.key(), ctx.bumps.poster_nonce)?;
+        child.version = TASK_ESCROW_VERSION;
+        child.bump = ctx.bumps.task_escrow;
+        child.parent = Some(parent.key());
+        list_on_board(&ctx.accounts.task_board, child, required_skills)?;
+        sync_board_bounty(&ctx.accounts.parent_board, &ctx.accounts.parent_tier_board, parent)?;
+
+        msg!("Subtask {} delegated with {} lamports from {}", child.task_id, amount, parent.task_id);
+        Ok(())
+    }
+
+    // 15. Initialize Recurring Task: Poster pre-funds `periods` instances of the same task.
+    // Each period's deposit covers the bounty plus the instance's rent, so cranking is free.
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_recurring(
+        ctx: Context<InitializeRecurring>,
+        recurring_id: String,
+        bounty_per_period: u64,
+        periods: u32,
+        period_secs: i64,
+        first_open_at: i64,
+        title: String,
+        spec_uri: String,
+        spec_hash: [u8; 32],
+        agent: Option<Pubkey>,
+    ) -> Result<()> {
+        require!(recurring_id.len() <= 40, EscrowError::TaskIdTooLong);
+        require!(title.len() <= 64, EscrowError::TitleTooLong);
+        require!(spec_uri.len() <= 256, EscrowError::UrlTooLong);
+        require!(periods > 0 && period_secs > 0 && bounty_per_period > 0, EscrowError::InvalidSchedule);
+
+        let instance_rent = Rent::get()?.minimum_balance(8 + TaskEscrow::INIT_SPACE);
+        let deposit = bounty_per_period
+            .checked_add(instance_rent)
+            .and_then(|per_period| per_period.checked_mul(periods as u64))
+            .ok_or(EscrowError::Overflow)?;
+
+        let recurring = &mut ctx.accounts.recurring_task;
+        recurring.poster = ctx.accounts.poster.key();
+        recurring.recurring_id = recurring_id;
+        recurring.title = title;
+        recurring.spec_uri = spec_uri;
+        recurring.spec_hash = spec_hash;
+        recurring.agent = agent;
+        recurring.bounty_per_period = bounty_per_period;
+        recurring.instance_rent = instance_rent;
+        recurring.period_secs = period_secs;
+        recurring.periods_total = periods;
+        recurring.periods_opened = 0;
+        recurring.next_open_at = first_open_at;
+        recurring.bump = ctx.bumps.recurring_task;
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.poster.to_account_info(),
+            to: recurring.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, deposit)?;
+
+        msg!("Recurring task {} funded for {} periods", recurring.recurring_id, periods);
+        Ok(())
+    }
+
+    // 16. Open Period: Permissionless crank that opens the next funded instance once due.
+    // The cranker pays the instance rent up front and is reimbursed from the recurring deposit.
+    pub fn open_period(ctx: Context<OpenPeriod>) -> Result<()> {
+        let recurring = &mut ctx.accounts.recurring_task;
+        let now = Clock::get()?.unix_timestamp;
+        require!(recurring.periods_opened < recurring.periods_total, EscrowError::NoPeriodsLeft);
+        require!(now >= recurring.next_open_at, EscrowError::PeriodNotDue);
+
+        let period = recurring.periods_opened;
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        let recurring_info = recurring.to_account_info();
+        pay_from_escrow(&recurring_info, &task_escrow.to_account_info(), recurring.bounty_per_period)?;
+        pay_from_escrow(&recurring_info, &ctx.accounts.cranker, recurring.instance_rent)?;
+
+        task_escrow.poster = recurring.poster;
+        task_escrow.task_id = format!("{}#{}", recurring.recurring_id, period);
+        task_escrow.title = recurring.title.clone();
+        task_escrow.spec_uri = recurring.spec_uri.clone();
+        task_escrow.spec_hash = recurring.spec_hash;
+        task_escrow.bounty_amount = recurring.bounty_per_period;
+        task_escrow.total_funded = recurring.bounty_per_period;
+        task_escrow.created_at = now;
+        task_escrow.version = TASK_ESCROW_VERSION;
+        task_escrow.bump = ctx.bumps.task_escrow;
+        task_escrow.assigned_agent = recurring.agent;
+        if recurring.agent.is_some() {
+            task_escrow.status = TaskStatus::InProgress;
+            task_escrow.started_at = Some(now);
+        } else {
+            task_escrow.status = TaskStatus::Open;
+        }
+
+        list_on_board(&ctx.accounts.task_board, task_escrow, 0)?;
+
+        recurring.periods_opened += 1;
+        recurring.next_open_at += recurring.period_secs;
+
+        msg!("Opened period {} of {} for {}", period + 1, recurring.periods_total, recurring.recurring_id);
+        Ok(())
+    }
+
+    // 17. Cancel Recurring Task: Poster stops the schedule; unopened periods are refunded.
+    // Instances that are already open are unaffected and settle or cancel on their own.
+    pub fn cancel_recurring(ctx: Context<CancelRecurring>) -> Result<()> {
+        let recurring = &ctx.accounts.recurring_task;
+        msg!(
+            "Recurring task {} cancelled after {} of {} periods. Remaining deposit refunded.",
+            recurring.recurring_id,
+            recurring.periods_opened,
+            recurring.periods_total
+        );
+        Ok(())
+    }
+
+    // 18. Top Up Bounty: Anyone adds funds to a live task. Contributions are recorded
+    // per wallet so a later cancel can refund them pro-rata. This is also how backers
+    // fund a crowdfunded task; the task opens as soon as its goal is reached.
+    pub fn top_up_bounty(ctx: Context<TopUpBounty>, amount: u64) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(
+            task_escrow.status == TaskStatus::Open
+                || task_escrow.status == TaskStatus::InProgress
+                || task_escrow.status == TaskStatus::Funding,
+            EscrowError::InvalidStatus
+        );
+        require!(task_escrow.parent.is_none(), EscrowError::InvalidStatus);
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+        // The pool is split by contribution, so a later backer would dilute the earlier ones
+        require!(task_escrow.refund_pool == 0, EscrowError::RefundPoolOpen);
+        if task_escrow.status == TaskStatus::Funding {
+            let funding = task_escrow.funding.as_ref().ok_or(EscrowError::InvalidStatus)?;
+            require!(
+                Clock::get()?.unix_timestamp < funding.deadline,
+                EscrowError::FundingClosed
+            );
+        }
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.contributor.to_account_info(),
+            to: task_escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+
+        let contribution = &mut ctx.accounts.contribution;
+        if contribution.amount == 0 {
+            contribution.task_escrow = task_escrow.key();
+            contribution.contributor = ctx.accounts.contributor.key();
+            contribution.bump = ctx.bumps.contribution;
+            task_escrow.contributor_count += 1;
+        }
+        contribution.amount = contribution.amount.checked_add(amount).ok_or(EscrowError::Overflow)?;
+
+        task_escrow.bounty_amount = task_escrow.bounty_amount.checked_add(amount).ok_or(EscrowError::Overflow)?;
+        task_escrow.total_funded = task_escrow.total_funded.checked_add(amount).ok_or(EscrowError::Overflow)?;
+        task_escrow.contributed += amount;
+
+        if task_escrow.status == TaskStatus::Funding
+            && task_escrow.funding.as_ref().is_some_and(|f| task_escrow.bounty_amount >= f.goal)
+        {
+            task_escrow.status = TaskStatus::Open;
+            msg!("Funding goal reached. Task {} is open.", task_escrow.task_id);
+        }
+        sync_board_bounty(&ctx.accounts.task_board, &ctx.accounts.tier_board, task_escrow)?;
+
+        msg!("Bounty topped up by {} lamports. New bounty: {}", amount, task_escrow.bounty_amount);
+        Ok(())
+    }
+
+    // 19. Reduce Bounty: Poster withdraws part of their own funding. Once an agent is
+    // assigned, that agent must co-sign. Contributors' funds can't be withdrawn this way.
+    pub fn reduce_bounty(ctx: Context<ReduceBounty>, amount: u64) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(
+            task_escrow.status == TaskStatus::Open || task_escrow.status == TaskStatus::InProgress,
+            EscrowError::InvalidStatus
+        );
+        // A subtask's funds belong to its parent; they only flow back through cancellation
+        require!(task_escrow.parent.is_none(), EscrowError::InvalidStatus);
+        require!(!task_escrow.bidding_open, EscrowError::BiddingInProgress);
+        if let Some(assigned) = task_escrow.assigned_agent {
+            let agent = ctx.accounts.agent.as_ref().ok_or(EscrowError::AgentConsentRequired)?;
+            require!(agent.key() == assigned, EscrowError::WrongAgent);
+        }
+        if let Some(contest) = &task_escrow.contest {
+            require!(contest.entry_count == 0, EscrowError::ContestHasEntries);
+        }
+
+        // The poster can withdraw up to what their share of the bounty is currently worth,
+        // giving up that share in proportion
+        let poster_funded = task_escrow
+            .total_funded
+            .checked_sub(task_escrow.contributed)
+            .ok_or(EscrowError::Overflow)?;
+        let poster_value = pro_rata(task_escrow.bounty_amount, poster_funded, task_escrow.total_funded)?;
+        require!(
+            amount > 0 && amount <= poster_value && amount < task_escrow.bounty_amount,
+            EscrowError::InvalidAmount
+        );
+
+        pay_from_escrow(&task_escrow.to_account_info(), &ctx.accounts.poster.to_account_info(), amount)?;
+        task_escrow.bounty_amount -= amount;
+        task_escrow.total_funded -= pro_rata(poster_funded, amount, poster_value)?;
+        sync_board_bounty(&ctx.accounts.task_board, &ctx.accounts.tier_board, task_escrow)?;
+
+        msg!("Bounty reduced by {} lamports. New bounty: {}", amount, task_escrow.bounty_amount);
+        Ok(())
+    }
+
+    // 20. Claim Refund: Contributor collects their pro-rata share of a cancelled task plus
+    // their share of anything refunded earlier (an under-budget award or partial payout).
+    // On a paid-out task that is all there is besides the record's rent.
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        let contribution = &ctx.accounts.contribution;
+        let contributor = ctx.accounts.contributor.to_account_info();
+
+        let cancelled_share = match task_escrow.status {
+            TaskStatus::Cancelled => {
+                pro_rata(task_escrow.bounty_amount, contribution.amount, task_escrow.total_funded)?
+            }
+            TaskStatus::Completed => 0,
+            _ => return err!(EscrowError::InvalidStatus),
+        };
+        let refund = cancelled_share
+            .checked_add(pro_rata(task_escrow.refund_pool, contribution.amount, task_escrow.contributed)?)
+            .ok_or(EscrowError::Overflow)?;
+        if refund > 0 {
+            pay_from_escrow(&task_escrow.to_account_info(), &contributor, refund)?;
+        }
+        task_escrow.contributor_count -= 1;
+
+        // The last claim on a cancelled task closes it; dust and rent go to the poster
+        if task_escrow.status == TaskStatus::Cancelled && task_escrow.contributor_count == 0 {
+            task_escrow.close(ctx.accounts.poster.to_account_info())?;
+        }
+
+        msg!("Refunded {} lamports to contributor {}", refund, contributor.key());
+        Ok(())
+    }
+
+    // 21. Initialize Crowdfunded Task: Poster publishes a task that backers fund via
+    // top_up_bounty. It stays in Funding until `funding_goal` is met before the deadline,
+    // listed on the lowest tier and moving up as backers fund it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_crowdfunded_task(
+        ctx: Context<InitializeTask>,
+        task_id: String,
+        funding_goal: u64,
+        funding_deadline: i64,
+        title: String,
+        spec_uri: String,
+        spec_hash: [u8; 32],
+        required_skills: u64,
+    ) -> Result<()> {
+        require!(task_id.len() <= 50, EscrowError::TaskIdTooLong);
+        require!(title.len() <= 64, EscrowError::TitleTooLong);
+        require!(spec_uri.len() <= 256, EscrowError::UrlTooLong);
+        require!(funding_goal > 0, EscrowError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(funding_deadline > now, EscrowError::InvalidDeadline);
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        task_escrow.poster = ctx.accounts.poster.key();
+        task_escrow.task_id = task_id;
+        task_escrow.title = title;
+        task_escrow.spec_uri = spec_uri;
+        task_escrow.spec_hash = spec_hash;
+        task_escrow.bounty_amount = 0;
+        task_escrow.status = TaskStatus::Funding;
+        task_escrow.created_at = now;
+        task_escrow.nonce = take_task_nonce(&mut ctx.accounts.poster_nonce, ctx.accounts.poster.key(), ctx.bumps.poster_nonce)?;
+        task_escrow.version = TASK_ESCROW_VERSION;
+        task_escrow.bump = ctx.bumps.task_escrow;
+        task_escrow.funding = Some(FundingGoal {
+            goal: funding_goal,
+            deadline: funding_deadline,
+        });
+        list_on_board(&ctx.accounts.task_board, task_escrow, required_skills)?;
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskPosted,
+        )?;
+
+        msg!("Crowdfunded task {} seeking {} lamports", task_escrow.task_id, funding_goal);
+        Ok(())
+    }
+
+    // 22. Expire Funding: Permissionless. A task that missed its goal by the deadline is
+    // cancelled and every backer can claim_refund their share.
+    pub fn expire_funding(ctx: Context<ExpireFunding>) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Funding, EscrowError::InvalidStatus);
+        let funding = task_escrow.funding.as_ref().ok_or(EscrowError::InvalidStatus)?;
+        require!(
+            Clock::get()?.unix_timestamp >= funding.deadline,
+            EscrowError::FundingStillOpen
+        );
+
+        record_poster_event(
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+            PosterEvent::TaskCancelled { after_start: false },
+        )?;
+
+        task_escrow.status = TaskStatus::Cancelled;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+        if task_escrow.contributor_count == 0 {
+            task_escrow.close(ctx.accounts.poster.to_account_info())?;
+        }
+
+        msg!("Funding expired. Backers may claim refunds.");
+        Ok(())
+    }
+
+    // 23. Set Worker Bond: Poster requires workers to lock `amount` lamports, at assignment or,
+    // for workers who take an open task by submitting, at submission. The bond is returned on
+    // a pass or timeout and slashed to the poster on a fail or abandonment.
+    pub fn set_worker_bond(ctx: Context<UpdateTask>, amount: u64) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(
+            task_escrow.status == TaskStatus::Open || task_escrow.status == TaskStatus::Funding,
+            EscrowError::InvalidStatus
+        );
+        require!(task_escrow.assigned_agent.is_none(), EscrowError::InvalidStatus);
+        require!(task_escrow.contest.is_none(), EscrowError::ContestTask);
+
+        task_escrow.worker_bond = amount;
+
+        msg!("Worker bond for {} set to {} lamports", task_escrow.task_id, amount);
+        Ok(())
+    }
+
+    // 24. Migrate Task: Permissionless. Rewrites a task created before versioning into
+    // the current layout. The payer covers the extra rent; escrowed funds are untouched.
+    // The legacy free-text description becomes the title (truncated to 64 bytes).
+    pub fn migrate_task(ctx: Context<MigrateTask>) -> Result<()> {
+        let task_info = ctx.accounts.task_escrow.to_account_info();
+        let old_len = 8 + TaskEscrowV0::INIT_SPACE;
+        let new_len = 8 + TaskEscrow::INIT_SPACE;
+
+        let legacy = {
+            let data = task_info.try_borrow_data()?;
+            require!(data.len() == old_len, EscrowError::AlreadyMigrated);
+            require!(data[..8] == TaskEscrow::DISCRIMINATOR, EscrowError::AlreadyMigrated);
+            TaskEscrowV0::deserialize(&mut &data[8..])?
+        };
+
+        let mut title_len = legacy.description.len().min(64);
+        while !legacy.description.is_char_boundary(title_len) {
+            title_len -= 1;
+        }
+        let task_escrow = TaskEscrow {
+            version: TASK_ESCROW_VERSION,
+            poster: legacy.poster,
+            task_id: legacy.task_id,
+            title: legacy.description[..title_len].to_string(),
+            spec_uri: String::new(),
+            spec_hash: [0; 32],
+            bounty_amount: legacy.bounty_amount,
+            status: legacy.status,
+            assigned_agent: legacy.assigned_agent,
+            completed_agent: legacy.completed_agent,
+            created_at: legacy.created_at,
+            completed_at: legacy.completed_at,
+            bump: legacy.bump,
+            contest: None,
+            team: Vec::new(),
+            parent: None,
+            open_subtasks: 0,
+            total_funded: legacy.bounty_amount,
+            contributed: 0,
+            contributor_count: 0,
+            funding: None,
+            worker_bond: 0,
+            started_at: None,
+            required_skills: 0,
+            board_page: 0,
+            board_slot: 0,
+            board_tier: 0,
+            listed: false,
+            nonce: 0,
+            delegated: 0,
+            assignment_bond: 0,
+            bidding_open: false,
+            refund_pool: 0,
+            reserved: [0; 15],
+        };
+
+        let rent = Rent::get()?;
+        let extra_rent = rent.minimum_balance(new_len).saturating_sub(rent.minimum_balance(old_len));
+        if extra_rent > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: task_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, extra_rent)?;
+        }
+        task_info.realloc(new_len, true)?;
+        task_escrow.try_serialize(&mut &mut task_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Task {} migrated to layout v{}", task_escrow.task_id, TASK_ESCROW_VERSION);
+        Ok(())
+    }
+
+    // 25. Initialize Board Page: Anyone pays for an empty page of a bounty tier's task board.
+    pub fn initialize_board_page(ctx: Context<InitializeBoardPage>, tier: u8, page: u32) -> Result<()> {
+        require!((tier as usize) <= BOUNTY_TIER_LIMITS.len(), EscrowError::InvalidBoard);
+        let mut board = ctx.accounts.task_board.load_init()?;
+        board.tier = tier;
+        board.page = page;
+        msg!("Task board page {} created for tier {}", page, tier);
+        Ok(())
+    }
+
+    // 26. List Task: Poster lists an open task that predates the board (a migrated task) on
+    // the page for its bounty tier. New tasks are listed when they are created.
+    pub fn list_task(ctx: Context<ListTask>, required_skills: u64) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(!task_escrow.listed, EscrowError::AlreadyListed);
+        list_on_board(&ctx.accounts.task_board, task_escrow, required_skills)
+    }
+
+    // 27. Open Bidding: Poster asks for sealed bids on an open task. The escrowed bounty is
+    // the ceiling; agents commit to a price before `commit_deadline` and reveal it before
+    // `reveal_deadline`. Each commitment locks `bid_bond`, forfeited to the poster if the
+    // bid is never revealed.
+    pub fn open_bidding(
+        ctx: Context<OpenBidding>,
+        commit_deadline: i64,
+        reveal_deadline: i64,
+        bid_bond: u64,
+    ) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(
+            task_escrow.assigned_agent.is_none() && task_escrow.contest.is_none(),
+            EscrowError::InvalidStatus
+        );
+        require!(
+            commit_deadline > Clock::get()?.unix_timestamp && reveal_deadline > commit_deadline,
+            EscrowError::InvalidDeadline
+        );
+
+        let round = &mut ctx.accounts.bidding_round;
+        round.task_escrow = task_escrow.key();
+        round.poster = task_escrow.poster;
+        round.max_bid = task_escrow.bounty_amount;
+        round.commit_deadline = commit_deadline;
+        round.reveal_deadline = reveal_deadline;
+        round.bid_bond = bid_bond;
+        round.bump = ctx.bumps.bidding_round;
+        task_escrow.bidding_open = true;
+
+        msg!("Sealed bidding opened for {} until {}", task_escrow.task_id, commit_deadline);
+        Ok(())
+    }
+
+    // 28. Commit Bid: Agent (or an operator/session key with PERM_PLACE_BIDS) posts
+    // `sha256(amount_le || salt || agent)` and locks the bid bond.
+    pub fn commit_bid(ctx: Context<CommitBid>, agent: Pubkey, commitment: [u8; 32]) -> Result<()> {
+        let bidder = resolve_agent(
+            ctx.accounts.bidder.key(),
+            &ctx.accounts.operator_key,
+            &ctx.accounts.session_key,
+            PERM_PLACE_BIDS,
+        )?;
+        require!(bidder == agent, EscrowError::Unauthorized);
+
+        let round = &mut ctx.accounts.bidding_round;
+        require!(Clock::get()?.unix_timestamp < round.commit_deadline, EscrowError::CommitWindowClosed);
+
+        let bid = &mut ctx.accounts.sealed_bid;
+        bid.task_escrow = round.task_escrow;
+        bid.agent = agent;
+        bid.poster = round.poster;
+        bid.commitment = commitment;
+        bid.reveal_deadline = round.reveal_deadline;
+        bid.bump = ctx.bumps.sealed_bid;
+
+        if round.bid_bond > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.bidder.to_account_info(),
+                to: bid.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, round.bid_bond)?;
+            bid.bond = round.bid_bond;
+
+            if let Some(session_key) = &ctx.accounts.session_key {
+                record_session_spend(
+                    session_key,
+                    &ctx.accounts.escrow_authority,
+                    &ctx.accounts.registry_program,
+                    round.bid_bond,
+                )?;
+            }
+        }
+        round.bid_count += 1;
+
+        msg!("Sealed bid committed by {}", agent);
+        Ok(())
+    }
+
+    // 29. Reveal Bid: Opens a commitment during the reveal window and returns the bond.
+    // The lowest revealed bid wins; ties go to whoever revealed first.
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        let bid = &mut ctx.accounts.sealed_bid;
+        let bidder = resolve_agent(
+            ctx.accounts.bidder.key(),
+            &ctx.accounts.operator_key,
+            &ctx.accounts.session_key,
+            PERM_PLACE_BIDS,
+        )?;
+        require!(bidder == bid.agent, EscrowError::Unauthorized);
+
+        let round = &mut ctx.accounts.bidding_round;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= round.commit_deadline && now < round.reveal_deadline,
+            EscrowError::RevealWindowNotOpen
+        );
+        require!(bid.revealed_amount.is_none(), EscrowError::BidAlreadyRevealed);
+        require!(
+            hashv(&[&amount.to_le_bytes(), &salt, bid.agent.as_ref()]).to_bytes() == bid.commitment,
+            EscrowError::BidCommitmentMismatch
+        );
+        require!(amount > 0 && amount <= round.max_bid, EscrowError::InvalidAmount);
+
+        bid.revealed_amount = Some(amount);
+        pay_from_escrow(&bid.to_account_info(), &ctx.accounts.agent, bid.bond)?;
+        bid.bond = 0;
+
+        round.revealed_count += 1;
+        if round.best_bidder.is_none() || amount < round.best_amount {
+            round.best_bidder = Some(bid.agent);
+            round.best_amount = amount;
+        }
+
+        msg!("Bid of {} lamports revealed by {}", amount, bid.agent);
+        Ok(())
+    }
+
+    // 30. Award Bid: Once reveals close, the poster assigns the lowest bidder at their price
+    // and takes back the rest of the ceiling. With no reveals the task simply stays open.
+    pub fn award_bid(ctx: Context<AwardBid>) -> Result<()> {
+        let round = &ctx.accounts.bidding_round;
+        require!(Clock::get()?.unix_timestamp >= round.reveal_deadline, EscrowError::RevealWindowNotOpen);
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(task_escrow.status == TaskStatus::Open, EscrowError::InvalidStatus);
+        require!(task_escrow.assigned_agent.is_none(), EscrowError::InvalidStatus);
+        task_escrow.bidding_open = false;
+
+        let Some(winner) = round.best_bidder else {
+            msg!("No bids revealed for {}; task stays open", task_escrow.task_id);
+            return Ok(());
+        };
+
+        let excess = task_escrow
+            .bounty_amount
+            .checked_sub(round.best_amount)
+            .ok_or(EscrowError::InsufficientEscrow)?;
+        refund_funders(task_escrow, &ctx.accounts.poster.to_account_info(), excess)?;
+
+        task_escrow.assigned_agent = Some(winner);
+        task_escrow.status = TaskStatus::InProgress;
+        task_escrow.started_at = Some(Clock::get()?.unix_timestamp);
+        sync_board_bounty(&ctx.accounts.task_board, &ctx.accounts.tier_board, task_escrow)?;
+
+        msg!("Task {} awarded to {} for {} lamports", task_escrow.task_id, winner, round.best_amount);
+        Ok(())
+    }
+
+    // 31. Close Bid: Permissionless once reveals close. Rent goes back to the agent; the
+    // bond of a bid that was never revealed is forfeited to the poster.
+    pub fn close_bid(ctx: Context<CloseBid>) -> Result<()> {
+        let bid = &ctx.accounts.sealed_bid;
+        require!(Clock::get()?.unix_timestamp >= bid.reveal_deadline, EscrowError::RevealWindowNotOpen);
+
+        if bid.revealed_amount.is_none() {
+            pay_from_escrow(&bid.to_account_info(), &ctx.accounts.poster, bid.bond)?;
+            msg!("Unrevealed bid by {} forfeited {} lamports", bid.agent, bid.bond);
+        }
+        Ok(())
+    }
+
+    // 32. Submit Encrypted Result: Like submit_result, but `ciphertext_uri` holds the
+    // deliverable encrypted under a key only the worker (and, off-chain, the oracle) knows.
+    // `key_commitment = sha256(key_blob)` binds the blob release_key must later publish.
+    pub fn submit_encrypted_result(
+        ctx: Context<SubmitResult>,
+        ciphertext_uri: String,
+        ciphertext_hash: [u8; 32],
+        key_commitment: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.task_result.encryption = Some(EncryptedDelivery {
+            key_commitment,
+            verified_at: None,
+            released_key: Vec::new(),
+        });
+        submit_result(ctx, ciphertext_uri, ciphertext_hash)
+    }
+
+    // 33. Release Key: Worker publishes the key blob of a passed encrypted result and is paid
+    // in the same instruction. The blob is typically the key sealed to the poster's wallet.
+    // Publishing while the result is still pending only stores the blob; the verdict then
+    // pays directly. Team tasks pass the member wallets as remaining_accounts, in assignment order.
+    pub fn release_key<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseKey<'info>>,
+        key_blob: Vec<u8>,
+    ) -> Result<()> {
+        require!(key_blob.len() <= MAX_KEY_BLOB_LEN, EscrowError::KeyTooLong);
+        require!(ctx.accounts.task_escrow.status == TaskStatus::InProgress, EscrowError::InvalidStatus);
+
+        let result = &mut ctx.accounts.task_result;
+        require!(
+            result.status == ResultStatus::AwaitingKey || result.status == ResultStatus::Pending,
+            EscrowError::InvalidStatus
+        );
+        let encryption = result.encryption.as_mut().ok_or(EscrowError::NotEncrypted)?;
+        require!(encryption.released_key.is_empty(), EscrowError::KeyAlreadyReleased);
+        require!(
+            hashv(&[&key_blob]).to_bytes() == encryption.key_commitment,
+            EscrowError::KeyCommitmentMismatch
+        );
+        encryption.released_key = key_blob;
+
+        if result.status == ResultStatus::Pending {
+            msg!("Key published ahead of the verdict for {}", result.worker);
+            return Ok(());
+        }
+
+        // The oracle's verdict fixed the payout share before the key was released
+        let payout_bps = result.verdict.as_ref().ok_or(EscrowError::InvalidStatus)?.payout_bps;
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        pay_passed_result(
+            task_escrow,
+            result,
+            payout_bps,
+            &ctx.accounts.worker.to_account_info(),
+            &ctx.accounts.poster,
+            ctx.remaining_accounts,
+            &mut ctx.accounts.parent_task,
+            &ctx.accounts.parent_board,
+            &ctx.accounts.parent_tier_board,
+            &ctx.accounts.poster_profile,
+            &ctx.accounts.escrow_authority,
+            &ctx.accounts.registry_program,
+        )?;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        msg!("Key released. Bounty paid to {}", result.worker);
+        Ok(())
+    }
+
+    // 34. Reclaim Unreleased Key: If the worker sits on the key past the release timeout,
+    // the poster takes the bond, the result closes and the task reopens, as with a failed verdict.
+    pub fn reclaim_unreleased_key(ctx: Context<ReclaimUnreleasedKey>) -> Result<()> {
+        let result = &mut ctx.accounts.task_result;
+        require!(result.status == ResultStatus::AwaitingKey, EscrowError::InvalidStatus);
+        let verified_at = result
+            .encryption
+            .as_ref()
+            .and_then(|encryption| encryption.verified_at)
+            .ok_or(EscrowError::NotEncrypted)?;
+        require!(
+            Clock::get()?.unix_timestamp - verified_at > KEY_RELEASE_TIMEOUT_SECS,
+            EscrowError::TimeoutNotReached
+        );
+
+        pay_from_escrow(&result.to_account_info(), &ctx.accounts.poster.to_account_info(), result.bond)?;
+        result.close(ctx.accounts.worker.to_account_info())?;
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        task_escrow.assigned_agent = None;
+        task_escrow.team.clear();
+        task_escrow.started_at = None;
+        task_escrow.status = TaskStatus::Open;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        msg!("Key never released by {}. Task reopened.", result.worker);
+        Ok(())
+    }
+
+    // 35. Submit Committed Result: submit_result with an explicit commitment scheme.
+    // UrlHash is checked here (`url_hash == sha256(result_url)`); UrlAndDigest commits to
+    // `sha256(result_url || content_digest)` and is opened later with reveal_content_digest.
+    pub fn submit_committed_result(
+        ctx: Context<SubmitResult>,
+        result_url: String,
+        url_hash: [u8; 32],
+        commitment: CommitmentScheme,
+    ) -> Result<()> {
+        if commitment == CommitmentScheme::UrlHash {
+            require!(
+                hashv(&[result_url.as_bytes()]).to_bytes() == url_hash,
+                EscrowError::CommitmentMismatch
+            );
+        }
+        ctx.accounts.task_result.commitment = commitment;
+        submit_result(ctx, result_url, url_hash)
+    }
+
+    // 36. Reveal Content Digest: Worker opens a UrlAndDigest commitment before the verdict.
+    // `content_digest` is the sha256 of the delivered artifact the oracle will evaluate.
+    pub fn reveal_content_digest(ctx: Context<RevealContentDigest>, content_digest: [u8; 32]) -> Result<()> {
+        let result = &mut ctx.accounts.task_result;
+        require!(result.status == ResultStatus::Pending, EscrowError::AlreadySettled);
+        require!(result.commitment == CommitmentScheme::UrlAndDigest, EscrowError::CommitmentMismatch);
+        require!(result.content_digest.is_none(), EscrowError::DigestAlreadyRevealed);
+        require!(
+            hashv(&[result.result_url.as_bytes(), &content_digest]).to_bytes() == result.url_hash,
+            EscrowError::CommitmentMismatch
+        );
+
+        result.content_digest = Some(content_digest);
+
+        msg!("Content digest revealed for result of {}", result.task_escrow);
+        Ok(())
+    }
+
+    // 37. Attach Git Deliverable: Worker pins a code result to an exact commit before the
+    // verdict. `repository` is "host/owner/name"; commit and tree ids use the repository's
+    // object format (SHA-1 or SHA-256). Set once; the oracle checks the commit it names.
+    pub fn attach_git_deliverable(
+        ctx: Context<AttachGitDeliverable>,
+        repository: String,
+        commit: GitObjectId,
+        tree: GitObjectId,
+        pull_request: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            !repository.is_empty() && repository.len() <= MAX_REPOSITORY_LEN,
+            EscrowError::InvalidGitDeliverable
+        );
+        require!(
+            std::mem::discriminant(&commit) == std::mem::discriminant(&tree),
+            EscrowError::InvalidGitDeliverable
+        );
+
+        let result = &mut ctx.accounts.task_result;
+        require!(result.status == ResultStatus::Pending, EscrowError::AlreadySettled);
+        require!(result.git.is_none(), EscrowError::GitDeliverableAttached);
+
+        result.git = Some(GitDeliverable {
+            repository,
+            commit,
+            tree,
+            pull_request,
+        });
+
+        msg!("Git deliverable attached for result of {}", result.task_escrow);
+        Ok(())
+    }
+
+    // 38. Cancel Subtask: The parent's poster cancels a subtask that has gone unsettled for
+    // SUBTASK_STALE_SECS since work started (or since creation if it never did), so a
+    // delegating agent can't block the parent forever. A submitted result must be settled
+    // first. The bounty flows back into the parent and the rent goes to the delegating agent.
+    pub fn cancel_subtask(ctx: Context<CancelSubtask>) -> Result<()> {
+        let parent_poster = ctx.accounts.parent_task.as_ref().ok_or(EscrowError::ParentMismatch)?.poster;
+        require!(parent_poster == ctx.accounts.parent_poster.key(), EscrowError::Unauthorized);
+
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(
+            task_escrow.status != TaskStatus::Completed && task_escrow.status != TaskStatus::Cancelled,
+            EscrowError::InvalidStatus
+        );
+        require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
+        require!(
+            load_result(&ctx.accounts.task_result)?.filter(is_unsettled).is_none(),
+            EscrowError::ResultPending
+        );
+        let last_activity = task_escrow.started_at.unwrap_or(task_escrow.created_at);
+        require!(
+            Clock::get()?.unix_timestamp > last_activity + SUBTASK_STALE_SECS,
+            EscrowError::SubtaskNotStale
+        );
+
+        let refund = task_escrow.bounty_amount;
+        release_parent(
+            task_escrow,
+            &mut ctx.accounts.parent_task,
+            refund,
+            &ctx.accounts.parent_board,
+            &ctx.accounts.parent_tier_board,
+        )?;
+        if task_escrow.assignment_bond > 0 {
+            let agent = ctx.accounts.agent.as_ref().ok_or(EscrowError::WrongAgent)?;
+            pay_from_escrow(&task_escrow.to_account_info(), agent, task_escrow.assignment_bond)?;
+            task_escrow.assignment_bond = 0;
+        }
+        task_escrow.status = TaskStatus::Cancelled;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+        task_escrow.close(ctx.accounts.delegator.to_account_info())?;
+
+        msg!("Stale subtask cancelled; {} lamports returned to the parent", refund);
+        Ok(())
+    }
+
+    // 39. Reclaim Abandoned: The poster reopens a task whose assigned agent (or team) hasn't
+    // submitted within ABANDON_TIMEOUT_SECS of starting. The assignment bond is slashed.
+    pub fn reclaim_abandoned(ctx: Context<ReclaimAbandoned>) -> Result<()> {
+        let task_escrow = &mut ctx.accounts.task_escrow;
+        require!(
+            task_escrow.status == TaskStatus::InProgress && task_escrow.assigned_agent.is_some(),
+            EscrowError::InvalidStatus
+        );
+        require!(ctx.accounts.task_result.data_is_empty(), EscrowError::AlreadySubmitted);
+        require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
+        let started_at = task_escrow.started_at.unwrap_or(task_escrow.created_at);
+        require!(
+            Clock::get()?.unix_timestamp > started_at + ABANDON_TIMEOUT_SECS,
+            EscrowError::NotAbandoned
+        );
+
+        let slashed = task_escrow.assignment_bond;
+        if slashed > 0 {
+            pay_from_escrow(&task_escrow.to_account_info(), &ctx.accounts.poster.to_account_info(), slashed)?;
+            task_escrow.assignment_bond = 0;
+        }
+        let agent = task_escrow.assigned_agent.take();
+        task_escrow.team.clear();
+        task_escrow.started_at = None;
+        task_escrow.status = TaskStatus::Open;
+        sync_board(&ctx.accounts.task_board, task_escrow)?;
+
+        msg!("Task reclaimed from {:?}; {} lamports of bond slashed", agent, slashed);
+        Ok(())
+    }
+}
+
+/// Hands out the poster's next task nonce (the one the new task's seeds were derived from).
+fn take_task_nonce(poster_nonce: &mut Account<PosterNonce>, poster: Pubkey, bump: u8) -> Result<u64> {
+    poster_nonce.poster = poster;
+    poster_nonce.bump = bump;
+    let nonce = poster_nonce.next_task;
+    poster_nonce.next_task = nonce.checked_add(1).ok_or(EscrowError::Overflow)?;
+    Ok(nonce)
+}
+
+/// Pays out a result that passed verification: `payout_bps` of the bounty to the worker
+/// (or split across the team) and the rest refunded, bond back to the worker, task
+/// completed and the poster's stats updated.
+#[allow(clippy::too_many_arguments)]
+fn pay_passed_result<'info>(
+    task_escrow: &mut Account<'info, TaskEscrow>,
+    result: &mut Account<'info, TaskResult>,
+    payout_bps: u16,
+    worker: &AccountInfo<'info>,
+    poster: &AccountInfo<'info>,
+    members: &[AccountInfo<'info>],
+    parent_task: &mut Option<Account<'info, TaskEscrow>>,
+    parent_board: &Option<AccountLoader<'info, TaskBoard>>,
+    parent_tier_board: &Option<AccountLoader<'info, TaskBoard>>,
+    poster_profile: &UncheckedAccount<'info>,
+    escrow_authority: &Option<UncheckedAccount<'info>>,
+    registry_program: &Option<Program<'info, Registry>>,
+) -> Result<()> {
+    require!(task_escrow.open_subtasks == 0, EscrowError::OpenSubtasks);
+
+    let worker_amount = pro_rata(task_escrow.bounty_amount, payout_bps as u64, BPS_DENOMINATOR)?;
+    let poster_refund = task_escrow.bounty_amount - worker_amount;
+    release_bounty(
+        &task_escrow.to_account_info(),
+        &task_escrow.team,
+        worker,
+        members,
+        worker_amount,
+    )?;
+    // The unpaid share goes back to the funders pro-rata (backers claim theirs), or
+    // into the parent's bounty for a subtask
+    if poster_refund > 0 && task_escrow.parent.is_none() {
+        refund_funders(task_escrow, poster, poster_refund)?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    task_escrow.status = TaskStatus::Completed;
+    task_escrow.completed_agent = Some(result.worker);
+    task_escrow.completed_at = Some(now);
+    result.status = ResultStatus::Verified;
+    result.settlement = Some(Settlement { worker_amount, poster_refund });
+    release_parent(task_escrow, parent_task, poster_refund, parent_board, parent_tier_board)?;
+
+    record_poster_event(
+        poster_profile,
+        escrow_authority,
+        registry_program,
+        PosterEvent::TaskPaid {
+            amount: worker_amount,
+            verify_secs: task_escrow.started_at.map(|started| (now - started) as u64),
+        },
+    )?;
+
+    // Bond goes back to the worker with the bounty
+    pay_from_escrow(&result.to_account_info(), worker, result.bond)
+}
+
+/// Index into `BOUNTY_TIER_LIMITS` of the tier `amount` falls in.
+fn bounty_tier(amount: u64) -> u8 {
+    BOUNTY_TIER_LIMITS.iter().take_while(|limit| amount >= **limit).count() as u8
+}
+
+/// Mirrors a listed task's status and bounty onto its board entry, and frees the slot
+/// once the task is completed or cancelled. No-op for unlisted tasks.
+fn sync_board(task_board: &Option<AccountLoader<TaskBoard>>, task_escrow: &mut Account<TaskEscrow>) -> Result<()> {
+    if !task_escrow.listed {
+        return Ok(());
+    }
+    let mut board = task_board.as_ref().ok_or(EscrowError::InvalidBoard)?.load_mut()?;
+    let slot = task_escrow.board_slot as usize;
+    require!(
+        board.tier == task_escrow.board_tier
+            && board.page == task_escrow.board_page
+            && board.entries[slot].task == task_escrow.key(),
+        EscrowError::InvalidBoard
+    );
+
+    if task_escrow.status == TaskStatus::Completed || task_escrow.status == TaskStatus::Cancelled {
+        board.entries[slot] = BoardEntry::default();
+        board.count -= 1;
+        task_escrow.listed = false;
+    } else {
+        board.entries[slot].status = task_escrow.status.clone() as u8;
+        board.entries[slot].bounty_amount = task_escrow.bounty_amount;
+    }
+    Ok(())
+}
+
+/// Adds `task_escrow` to a free slot of `task_board`, which must be a page of its bounty tier.
+fn list_on_board(
+    task_board: &AccountLoader<TaskBoard>,
+    task_escrow: &mut Account<TaskEscrow>,
+    required_skills: u64,
+) -> Result<()> {
+    let mut board = task_board.load_mut()?;
+    require!(board.tier == bounty_tier(task_escrow.bounty_amount), EscrowError::InvalidBoard);
+    let slot = board
+        .entries
+        .iter()
+        .position(|entry| entry.task == Pubkey::default())
+        .ok_or(EscrowError::BoardPageFull)?;
+    board.entries[slot] = BoardEntry {
+        task: task_escrow.key(),
+        bounty_amount: task_escrow.bounty_amount,
+        required_skills,
+        status: task_escrow.status.clone() as u8,
+        _padding: [0; 7],
+    };
+    board.count += 1;
+
+    task_escrow.required_skills = required_skills;
+    task_escrow.board_tier = board.tier;
+    task_escrow.board_page = board.page;
+    task_escrow.board_slot = slot as u16;
+    task_escrow.listed = true;
+
+    msg!("Task {} listed on tier {} page {}", task_escrow.task_id, board.tier, board.page);
+    Ok(())
+}
+
+/// `sync_board` for instructions that change the bounty: a listed task whose bounty
+/// crossed a tier limit moves from its page to `tier_board`, a page of the new tier.
+fn sync_board_bounty(
+    task_board: &Option<AccountLoader<TaskBoard>>,
+    tier_board: &Option<AccountLoader<TaskBoard>>,
+    task_escrow: &mut Account<TaskEscrow>,
+) -> Result<()> {
+    sync_board(task_board, task_escrow)?;
+    if !task_escrow.listed || bounty_tier(task_escrow.bounty_amount) == task_escrow.board_tier {
+        return Ok(());
+    }
+    let tier_board = tier_board.as_ref().ok_or(EscrowError::BoardTierChanged)?;
+    {
+        // sync_board has already checked this is the task's page
+        let mut board = task_board.as_ref().ok_or(EscrowError::InvalidBoard)?.load_mut()?;
+        board.entries[task_escrow.board_slot as usize] = BoardEntry::default();
+        board.count -= 1;
+    }
+    task_escrow.listed = false;
+    let required_skills = task_escrow.required_skills;
+    list_on_board(tier_board, task_escrow, required_skills)
+}
+
+/// Hands `amount` of unspent bounty back to the funders pro-rata: the poster's share is
+/// paid now, contributors' shares go to `refund_pool` for claim_refund.
+fn refund_funders(task_escrow: &mut Account<TaskEscrow>, poster: &AccountInfo, amount: u64) -> Result<()> {
+    let poster_funded = task_escrow
+        .total_funded
+        .checked_sub(task_escrow.contributed)
+        .ok_or(EscrowError::Overflow)?;
+    let poster_share = pro_rata(amount, poster_funded, task_escrow.total_funded)?;
+    task_escrow.bounty_amount = task_escrow
+        .bounty_amount
+        .checked_sub(amount)
+        .ok_or(EscrowError::InsufficientEscrow)?;
+    task_escrow.refund_pool = task_escrow
+        .refund_pool
+        .checked_add(amount - poster_share)
+        .ok_or(EscrowError::Overflow)?;
+    pay_from_escrow(&task_escrow.to_account_info(), poster, poster_share)
+}
+
+/// `total * part / whole`, computed in u128 to avoid overflow.
+fn pro_rata(total: u64, part: u64, whole: u64) -> Result<u64> {
+    if whole == 0 {
+        return Ok(0);
+    }
+    u64::try_from(total as u128 * part as u128 / whole as u128).map_err(|_| error!(EscrowError::Overflow))
+}
+
+/// Settles a subtask against its parent: frees the parent's slot and moves `refund`
+/// lamports of unspent child funds back into the parent's bounty. No-op for root tasks.
+fn release_parent(
+    child: &mut Account<TaskEscrow>,
+    parent: &mut Option<Account<TaskEscrow>>,
+    refund: u64,
+    parent_board: &Option<AccountLoader<TaskBoard>>,
+    parent_tier_board: &Option<AccountLoader<TaskBoard>>,
+) -> Result<()> {
+    if child.parent.is_none() {
+        return Ok(());
+    }
+    let parent = parent.as_mut().ok_or(EscrowError::ParentMismatch)?;
+
+    if refund > 0 {
+        pay_from_escrow(&child.to_account_info(), &parent.to_account_info(), refund)?;
+        child.bounty_amount -= refund;
+        parent.bounty_amount = parent.bounty_amount.checked_add(refund).ok_or(EscrowError::Overflow)?;
+        parent.delegated = parent.delegated.saturating_sub(refund);
+        sync_board_bounty(parent_board, parent_tier_board, parent)?;
+    }
+    parent.open_subtasks -= 1;
+    Ok(())
+}
+
+/// Returns the agent a signer acts for: the signer itself, or the agent behind the
+/// operator or session key it presented (which must grant `permission`).
+fn resolve_agent(
+    signer: Pubkey,
+    operator_key: &Option<Account<OperatorKey>>,
+    session_key: &Option<Account<SessionKey>>,
+    permission: u8,
+) -> Result<Pubkey> {
+    if let Some(session_key) = session_key {
+        registry::check_session_authority(session_key.agent_wallet, signer, session_key, permission)?;
+        return Ok(session_key.agent_wallet);
+    }
+    if let Some(operator_key) = operator_key {
+        registry::check_agent_authority(operator_key.agent_wallet, signer, Some(operator_key), permission)?;
+        return Ok(operator_key.agent_wallet);
+    }
+    Ok(signer)
+}
+
+/// Charges lamports a session key committed against its spend cap in the registry.
+fn record_session_spend<'info>(
+    session_key: &Account<'info, SessionKey>,
+    escrow_authority: &Option<UncheckedAccount<'info>>,
+    registry_program: &Option<Program<'info, Registry>>,
+    amount: u64,
+) -> Result<()> {
+    let (Some(escrow_authority), Some(registry_program)) = (escrow_authority, registry_program) else {
+        return err!(EscrowError::MissingRegistryAccounts);
+    };
+
+    let (_, bump) = Pubkey::find_program_address(&[b"escrow_authority"], &crate::ID);
+    let signer_seeds: &[&[&[u8]]] = &[&[b"escrow_authority", &[bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        registry_program.to_account_info(),
+        registry::cpi::accounts::RecordSessionSpend {
+            session: session_key.to_account_info(),
+            authority: escrow_authority.to_account_info(),
+        },
+        signer_seeds,
+    );
+    registry::cpi::record_session_spend(cpi_ctx, amount)
+}
+
+/// Records a poster stat in the registry. Profiles are opt-in, so nothing is recorded
+/// until the poster registers one; after that every event is, since the PDA is fixed.
+fn record_poster_event<'info>(
+    poster_profile: &UncheckedAccount<'info>,
+    escrow_authority: &Option<UncheckedAccount<'info>>,
+    registry_program: &Option<Program<'info, Registry>>,
+    event: PosterEvent,
+) -> Result<()> {
+    if poster_profile.owner != &registry::ID {
+        return Ok(());
+    }
+    let (Some(escrow_authority), Some(registry_program)) = (escrow_authority, registry_program) else {
+        return err!(EscrowError::MissingRegistryAccounts);
+    };
+
+    let (_, bump) = Pubkey::find_program_address(&[b"escrow_authority"], &crate::ID);
+    let signer_seeds: &[&[&[u8]]] = &[&[b"escrow_authority", &[bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        registry_program.to_account_info(),
+        registry::cpi::accounts::RecordPosterEvent {
+            poster_account: poster_profile.to_account_info(),
+            authority: escrow_authority.to_account_info(),
+        },
+        signer_seeds,
+    );
+    registry::cpi::record_poster_event(cpi_ctx, event)
+}
+
+/// Feeds the verdict score into the worker's reputation when their registry profile is provided.
+fn record_verdict<'info>(
+    agent_account: &Option<Account<'info, AgentAccount>>,
+    agent_directory: &Option<UncheckedAccount<'info>>,
+    escrow_authority: &Option<UncheckedAccount<'info>>,
+    registry_program: &Option<Program<'info, Registry>>,
+    verdict: &Verdict,
+) -> Result<()> {
+    let Some(agent_account) = agent_account else {
+        return Ok(());
+    };
+    let (Some(escrow_authority), Some(registry_program)) = (escrow_authority, registry_program) else {
+        return err!(EscrowError::MissingRegistryAccounts);
+    };
+
+    let (_, bump) = Pubkey::find_program_address(&[b"escrow_authority"], &crate::ID);
+    let signer_seeds: &[&[&[u8]]] = &[&[b"escrow_authority", &[bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        registry_program.to_account_info(),
+        registry::cpi::accounts::RecordVerdict {
+            agent_account: agent_account.to_account_info(),
+            directory: agent_directory.as_ref().map(|d| d.to_account_info()),
+            authority: escrow_authority.to_account_info(),
+        },
+        signer_seeds,
+    );
+    registry::cpi::record_verdict(cpi_ctx, verdict.score, verdict.passed)
+}
+
+/// Pays `amount` to the worker, or splits it across the team when one is assigned.
+/// `members` must be the team wallets in assignment order; dust goes to the lead.
+fn release_bounty(
+    escrow: &AccountInfo,
+    team: &[TeamMember],
+    worker: &AccountInfo,
+    members: &[AccountInfo],
+    amount: u64,
+) -> Result<()> {
+    if team.is_empty() {
+        return pay_from_escrow(escrow, worker, amount);
+    }
+    require!(members.len() == team.len(), EscrowError::TeamAccountsMismatch);
+
+    let mut paid: u64 = 0;
+    for (member, info) in team.iter().zip(members) {
+        require!(info.key() == member.agent, EscrowError::TeamAccountsMismatch);
+        let share = pro_rata(amount, member.share_bps as u64, BPS_DENOMINATOR)?;
+        pay_from_escrow(escrow, info, share)?;
+        paid += share;
+    }
+    pay_from_escrow(escrow, &members[0], amount - paid)
+}
+
+/// The task's submitted result, if any. `task_result` is the `["result", task]` PDA,
+/// so only this program can have allocated it.
+fn load_result(task_result: &UncheckedAccount) -> Result<Option<TaskResult>> {
+    if task_result.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*task_result.owner, crate::ID, EscrowError::Unauthorized);
+    let data = task_result.try_borrow_data()?;
+    Ok(Some(TaskResult::try_deserialize(&mut &data[..])?))
+}
+
+/// Still waiting on a verdict or a key release, with the worker's bond locked in it.
+fn is_unsettled(result: &TaskResult) -> bool {
+    result.status == ResultStatus::Pending || result.status == ResultStatus::AwaitingKey
+}
+
+/// Closes a result held as an unchecked account; its bond and rent go to `to`.
+fn close_result(task_result: &AccountInfo, to: &AccountInfo) -> Result<()> {
+    pay_from_escrow(task_result, to, task_result.lamports())?;
+    task_result.assign(&anchor_lang::system_program::ID);
+    task_result.realloc(0, false)?;
+    Ok(())
+}
+
+/// Moves lamports out of a program-owned escrow PDA.
+fn pay_from_escrow(escrow: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **escrow.try_borrow_mut_lamports()? = escrow
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(EscrowError::InsufficientEscrow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(EscrowError::Overflow)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeTask<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = poster,
+        space = 8 + PosterNonce::INIT_SPACE,
+        seeds = [b"poster_nonce", poster.key().as_ref()],
+        bump
+    )]
+    pub poster_nonce: Account<'info, PosterNonce>,
+
+    #[account(
+        init,
+        payer = poster,
+        space = 8 + TaskEscrow::INIT_SPACE,
+        seeds = [b"task", poster.key().as_ref(), &poster_nonce.next_task.to_le_bytes()],
+        bump
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Page of the new task's bounty tier; every task is listed when it is created
+    #[account(mut)]
+    pub task_board: AccountLoader<'info, TaskBoard>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", poster.key().as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeOracle<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + OracleConfig::INIT_SPACE,
+        seeds = [b"oracle_config"],
+        bump
+    )]
+    pub oracle_config: Account<'info, OracleConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(result_url: String)]
+pub struct SubmitResult<'info> {
+    /// The agent, or one of its operator keys (which then pays rent and bond)
+    #[account(mut)]
+    pub worker: Signer<'info>,
+
+    /// Registry operator key, when `worker` is acting for an agent
+    pub operator_key: Option<Account<'info, OperatorKey>>,
+
+    /// Registry session key, when `worker` is an agent's ephemeral key
+    #[account(mut)]
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    #[account(
+        init,
+        payer = worker,
+        space = 8 + TaskResult::INIT_SPACE,
+        seeds = [b"result", task_escrow.key().as_ref()],
+        bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PDA that signs registry CPIs (session spend tracking)
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyResult<'info> {
+    pub oracle: Signer<'info>,
+
+    #[account(seeds = [b"oracle_config"], bump)]
+    pub oracle_config: Account<'info, OracleConfig>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_escrow.key().as_ref()],
+        bump = task_result.bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+
+    /// CHECK: Worker wallet to receive bounty on pass
+    #[account(mut, constraint = worker.key() == task_result.worker @ EscrowError::WrongAgent)]
+    pub worker: AccountInfo<'info>,
+
+    /// CHECK: Receives the worker's bond on a fail and its part of a partial payout's remainder
+    #[account(mut, address = task_escrow.poster @ EscrowError::Unauthorized)]
+    pub poster: AccountInfo<'info>,
+
+    /// Required when `task_escrow` is a subtask
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// Parent's board page and, if the refund moves it across tiers, a page of its new tier
+    #[account(mut)]
+    pub parent_board: Option<AccountLoader<'info, TaskBoard>>,
+    #[account(mut)]
+    pub parent_tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", poster.key().as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// Worker's registry profile; the verdict score feeds its reputation when provided
+    #[account(mut, seeds = [b"agent", worker.key().as_ref()], bump, seeds::program = registry::ID)]
+    pub agent_account: Option<Account<'info, AgentAccount>>,
+
+    /// CHECK: Worker's directory page, validated by the registry; required once the agent is listed
+    #[account(mut)]
+    pub agent_directory: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimTimeout<'info> {
+    #[account(mut)]
+    pub worker: Signer<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_escrow.key().as_ref()],
+        bump = task_result.bump,
+        constraint = task_result.worker == worker.key() @ EscrowError::Unauthorized
+    )]
+    pub task_result: Account<'info, TaskResult>,
+
+    /// Required when `task_escrow` is a subtask
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", task_escrow.poster.as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct AssignAgent<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+    /// The agent's co-signature; required on bonded tasks, since they post the bond
+    #[account(mut)]
+    pub agent: Option<Signer<'info>>,
+    #[account(
+        mut,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTask<'info> {
+    pub poster: Signer<'info>,
+    #[account(
+        mut,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitEntry<'info> {
+    #[account(mut)]
+    pub worker: Signer<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    #[account(
+        init,
+        payer = worker,
+        space = 8 + TaskResult::INIT_SPACE,
+        seeds = [b"entry", task_escrow.key().as_ref(), worker.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, TaskResult>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleContest<'info> {
+    /// Either the poster or the registered oracle
+    pub authority: Signer<'info>,
+
+    /// Only needed when the oracle is the one settling
+    #[account(seeds = [b"oracle_config"], bump)]
+    pub oracle_config: Option<Account<'info, OracleConfig>>,
+
+    #[account(mut, has_one = poster @ EscrowError::Unauthorized)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: Receives the poster's part of unallocated shares. Checked against task_escrow.poster.
+    #[account(mut)]
+    pub poster: AccountInfo<'info>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", task_escrow.poster.as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEntry<'info> {
+    #[account(mut)]
+    pub worker: Signer<'info>,
+
+    /// CHECK: May already be closed if the task was cancelled; inspected in the handler.
+    #[account(address = entry.task_escrow)]
+    pub task_escrow: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = worker,
+        seeds = [b"entry", task_escrow.key().as_ref(), worker.key().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, TaskResult>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateSubtask<'info> {
+    #[account(mut)]
+    pub agent: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = parent_task.assigned_agent == Some(agent.key()) @ EscrowError::WrongAgent
+    )]
+    pub parent_task: Account<'info, TaskEscrow>,
+
+    /// The agent posts the subtask, so it is numbered from the agent's own nonce
+    #[account(
+        init_if_needed,
+        payer = agent,
+        space = 8 + PosterNonce::INIT_SPACE,
+        seeds = [b"poster_nonce", agent.key().as_ref()],
+        bump
+    )]
+    pub poster_nonce: Account<'info, PosterNonce>,
+
+    #[account(
+        init,
+        payer = agent,
+        space = 8 + TaskEscrow::INIT_SPACE,
+        seeds = [b"task", agent.key().as_ref(), &poster_nonce.next_task.to_le_bytes()],
+        bump
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Page of the new task's bounty tier; every task is listed when it is created
+    #[account(mut)]
+    pub task_board: AccountLoader<'info, TaskBoard>,
+
+    /// Board page the parent is listed on; required once it is listed
+    #[account(mut)]
+    pub parent_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// Page of the parent's new tier; required when the carve-out moves it across tiers
+    #[account(mut)]
+    pub parent_tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(recurring_id: String)]
+pub struct InitializeRecurring<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    #[account(
+        init,
+        payer = poster,
+        space = 8 + RecurringTask::INIT_SPACE,
+        seeds = [b"recurring", poster.key().as_ref(), recurring_id.as_bytes()],
+        bump
+    )]
+    pub recurring_task: Account<'info, RecurringTask>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPeriod<'info> {
+    /// Anyone may crank; rent is reimbursed from the recurring deposit
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"recurring", recurring_task.poster.as_ref(), recurring_task.recurring_id.as_bytes()],
+        bump = recurring_task.bump
+    )]
+    pub recurring_task: Account<'info, RecurringTask>,
+
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + TaskEscrow::INIT_SPACE,
+        seeds = [b"task", recurring_task.key().as_ref(), &recurring_task.periods_opened.to_le_bytes()],
+        bump
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Page of the new task's bounty tier; every task is listed when it is created
+    #[account(mut)]
+    pub task_board: AccountLoader<'info, TaskBoard>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurring<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    #[account(
+        mut,
+        close = poster,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub recurring_task: Account<'info, RecurringTask>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpBounty<'info> {
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    #[account(
+        init_if_needed,
+        payer = contributor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", task_escrow.key().as_ref(), contributor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// Page of the bounty's new tier; required when the change moves a listed task across tiers
+    #[account(mut)]
+    pub tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReduceBounty<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    /// Assigned agent's co-signature, required once the task is in progress
+    pub agent: Option<Signer<'info>>,
+
+    #[account(
+        mut,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// Page of the bounty's new tier; required when the change moves a listed task across tiers
+    #[account(mut)]
+    pub tier_board: Option<AccountLoader<'info, TaskBoard>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    /// CHECK: Receives the remainder when the last refund closes the task
+    #[account(mut, address = task_escrow.poster @ EscrowError::Unauthorized)]
+    pub poster: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    #[account(
+        mut,
+        close = contributor,
+        seeds = [b"contribution", task_escrow.key().as_ref(), contributor.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireFunding<'info> {
+    /// CHECK: Receives the rent if no one backed the task
+    #[account(mut, address = task_escrow.poster @ EscrowError::Unauthorized)]
+    pub poster: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", task_escrow.poster.as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteTask<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+    
+    /// CHECK: The agent account to receive funds. Verified by logic if assigned_agent is set.
+    #[account(mut)]
+    pub agent: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: The task's result PDA; a result awaiting its verdict is closed to the agent
+    #[account(mut, seeds = [b"result", task_escrow.key().as_ref()], bump)]
+    pub task_result: UncheckedAccount<'info>,
+
+    /// Required when `task_escrow` is a subtask
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", poster.key().as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTask<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+    
+    #[account(
+        mut,
+        // Closed in the handler (rent + bounty back to poster) unless contributors need refunds
+        constraint = task_escrow.poster == poster.key() @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: The task's result PDA; cancelling is refused while a result is unsettled
+    #[account(seeds = [b"result", task_escrow.key().as_ref()], bump)]
+    pub task_result: UncheckedAccount<'info>,
+
+    /// Required when `task_escrow` is a subtask
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// Parent's board page and, if the refund moves it across tiers, a page of its new tier
+    #[account(mut)]
+    pub parent_board: Option<AccountLoader<'info, TaskBoard>>,
+    #[account(mut)]
+    pub parent_tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: The assigned agent; required while they have an assignment bond to get back
+    #[account(mut, constraint = agent.key() == task_escrow.assigned_agent.unwrap_or_default() @ EscrowError::WrongAgent)]
+    pub agent: Option<AccountInfo<'info>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", poster.key().as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8, page: u32)]
+pub struct InitializeBoardPage<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<TaskBoard>(),
+        seeds = [b"board", tier.to_le_bytes().as_ref(), page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub task_board: AccountLoader<'info, TaskBoard>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListTask<'info> {
+    pub poster: Signer<'info>,
+    #[account(
+        mut,
+        has_one = poster @ EscrowError::Unauthorized
+    )]
+    pub task_escrow: Account<'info, TaskEscrow>,
+    #[account(mut)]
+    pub task_board: AccountLoader<'info, TaskBoard>,
+}
+
+#[derive(Accounts)]
+pub struct OpenBidding<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+    #[account(mut, has_one = poster @ EscrowError::Unauthorized)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+    #[account(
+        init,
+        payer = poster,
+        space = 8 + BiddingRound::INIT_SPACE,
+        seeds = [b"bidding", task_escrow.key().as_ref()],
+        bump
+    )]
+    pub bidding_round: Account<'info, BiddingRound>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent: Pubkey)]
+pub struct CommitBid<'info> {
+    /// The agent, or one of its operator/session keys (which then pays rent and bond)
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// Registry operator key, when `bidder` is acting for an agent
+    pub operator_key: Option<Account<'info, OperatorKey>>,
+
+    /// Registry session key, when `bidder` is an agent's ephemeral key
+    #[account(mut)]
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    #[account(mut, seeds = [b"bidding", bidding_round.task_escrow.as_ref()], bump = bidding_round.bump)]
+    pub bidding_round: Account<'info, BiddingRound>,
+
+    #[account(
+        init,
+        payer = bidder,
+        space = 8 + SealedBid::INIT_SPACE,
+        seeds = [b"bid", bidding_round.task_escrow.as_ref(), agent.as_ref()],
+        bump
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PDA that signs registry CPIs (session spend tracking)
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+    pub operator_key: Option<Account<'info, OperatorKey>>,
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    #[account(mut, seeds = [b"bidding", sealed_bid.task_escrow.as_ref()], bump = bidding_round.bump)]
+    pub bidding_round: Account<'info, BiddingRound>,
+
+    #[account(
+        mut,
+        seeds = [b"bid", sealed_bid.task_escrow.as_ref(), sealed_bid.agent.as_ref()],
+        bump = sealed_bid.bump
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+
+    /// CHECK: Receives the returned bond. Checked against sealed_bid.agent.
+    #[account(mut, address = sealed_bid.agent @ EscrowError::WrongAgent)]
+    pub agent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AwardBid<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+    #[account(mut, has_one = poster @ EscrowError::Unauthorized)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+    /// Page of the winning bid's tier; required when the award moves the task across tiers
+    #[account(mut)]
+    pub tier_board: Option<AccountLoader<'info, TaskBoard>>,
+    #[account(
+        mut,
+        close = poster,
+        seeds = [b"bidding", task_escrow.key().as_ref()],
+        bump = bidding_round.bump
+    )]
+    pub bidding_round: Account<'info, BiddingRound>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBid<'info> {
+    #[account(
+        mut,
+        close = agent,
+        seeds = [b"bid", sealed_bid.task_escrow.as_ref(), sealed_bid.agent.as_ref()],
+        bump = sealed_bid.bump
+    )]
+    pub sealed_bid: Account<'info, SealedBid>,
+    /// CHECK: Receives the rent. Checked against sealed_bid.agent.
+    #[account(mut, address = sealed_bid.agent @ EscrowError::WrongAgent)]
+    pub agent: AccountInfo<'info>,
+    /// CHECK: Receives a forfeited bond. Checked against sealed_bid.poster.
+    #[account(mut, address = sealed_bid.poster @ EscrowError::Unauthorized)]
+    pub poster: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseKey<'info> {
+    #[account(mut, address = task_result.worker @ EscrowError::WrongAgent)]
+    pub worker: Signer<'info>,
+
+    #[account(mut)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_escrow.key().as_ref()],
+        bump = task_result.bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+
+    /// CHECK: Receives the poster's part of the unpaid share of a partial-credit verdict
+    #[account(mut, address = task_escrow.poster @ EscrowError::Unauthorized)]
+    pub poster: AccountInfo<'info>,
+
+    /// Required when `task_escrow` is a subtask
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// Parent's board page and, if the refund moves it across tiers, a page of its new tier
+    #[account(mut)]
+    pub parent_board: Option<AccountLoader<'info, TaskBoard>>,
+    #[account(mut)]
+    pub parent_tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: Poster's registry profile PDA. Stats are recorded whenever it exists
+    #[account(mut, seeds = [b"poster", task_escrow.poster.as_ref()], bump, seeds::program = registry::ID)]
+    pub poster_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs registry CPIs
+    #[account(seeds = [b"escrow_authority"], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    pub registry_program: Option<Program<'info, Registry>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealContentDigest<'info> {
+    #[account(address = task_result.worker @ EscrowError::WrongAgent)]
+    pub worker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_result.task_escrow.as_ref()],
+        bump = task_result.bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+}
+
+#[derive(Accounts)]
+pub struct AttachGitDeliverable<'info> {
+    #[account(address = task_result.worker @ EscrowError::WrongAgent)]
+    pub worker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_result.task_escrow.as_ref()],
+        bump = task_result.bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimAbandoned<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    #[account(mut, has_one = poster @ EscrowError::Unauthorized)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// CHECK: The task's result PDA; must still be empty (nothing submitted)
+    #[account(seeds = [b"result", task_escrow.key().as_ref()], bump)]
+    pub task_result: UncheckedAccount<'info>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubtask<'info> {
+    pub parent_poster: Signer<'info>,
+
+    #[account(mut, constraint = task_escrow.parent.is_some() @ EscrowError::ParentMismatch)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the subtask is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: The subtask's result PDA; cancelling is refused while a result is unsettled
+    #[account(seeds = [b"result", task_escrow.key().as_ref()], bump)]
+    pub task_result: UncheckedAccount<'info>,
+
+    /// The subtask's parent; its poster must sign
+    #[account(mut, constraint = parent_task.key() == task_escrow.parent.unwrap_or_default() @ EscrowError::ParentMismatch)]
+    pub parent_task: Option<Account<'info, TaskEscrow>>,
+
+    /// Parent's board page and, if the refund moves it across tiers, a page of its new tier
+    #[account(mut)]
+    pub parent_board: Option<AccountLoader<'info, TaskBoard>>,
+    #[account(mut)]
+    pub parent_tier_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    /// CHECK: The delegating agent, who paid the subtask's rent
+    #[account(mut, address = task_escrow.poster @ EscrowError::Unauthorized)]
+    pub delegator: AccountInfo<'info>,
+
+    /// CHECK: The subtask's assigned agent; required while they have an assignment bond to get back
+    #[account(mut, constraint = agent.key() == task_escrow.assigned_agent.unwrap_or_default() @ EscrowError::WrongAgent)]
+    pub agent: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimUnreleasedKey<'info> {
+    #[account(mut)]
+    pub poster: Signer<'info>,
+
+    #[account(mut, has_one = poster @ EscrowError::Unauthorized)]
+    pub task_escrow: Account<'info, TaskEscrow>,
+
+    /// Board page the task is listed on; required once it is listed
+    #[account(mut)]
+    pub task_board: Option<AccountLoader<'info, TaskBoard>>,
+
+    #[account(
+        mut,
+        seeds = [b"result", task_escrow.key().as_ref()],
+        bump = task_result.bump
+    )]
+    pub task_result: Account<'info, TaskResult>,
+
+    /// CHECK: Gets the result's rent back when it closes
+    #[account(mut, address = task_result.worker @ EscrowError::WrongAgent)]
+    pub worker: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTask<'info> {
+    /// CHECK: legacy layout no longer deserializes as `TaskEscrow`; ownership,
+    /// discriminator and length are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub task_escrow: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TaskEscrow {
+    /// Layout version, see `TASK_ESCROW_VERSION`
+    pub version: u8,
+    pub poster: Pubkey,
+    #[max_len(50)]
+    pub task_id: String,
+    #[max_len(64)]
+    pub title: String,
+    #[max_len(256)]
+    pub spec_uri: String,
+    pub spec_hash: [u8; 32],
+    pub bounty_amount: u64,
+    pub status: TaskStatus,
+    pub assigned_agent: Option<Pubkey>,
+    pub completed_agent: Option<Pubkey>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub bump: u8,
+    pub contest: Option<ContestConfig>,
+    #[max_len(MAX_TEAM_SIZE)]
+    pub team: Vec<TeamMember>,
+    pub parent: Option<Pubkey>,
+    pub open_subtasks: u8,
+    /// Everything ever deposited, net of bounty reductions. Funders own the bounty (and
+    /// any refund) in proportion to their part of this.
+    pub total_funded: u64,
+    /// Portion of `total_funded` that came from top-ups
+    pub contributed: u64,
+    pub contributor_count: u32,
+    pub funding: Option<FundingGoal>,
+    /// Lamports a worker must lock when submitting a result (0 = no bond)
+    pub worker_bond: u64,
+    /// When an agent first started work (assignment or first submission)
+    pub started_at: Option<i64>,
+    /// Skill bitmap the poster listed the task under
+    pub required_skills: u64,
+    pub board_page: u32,
+    pub board_slot: u16,
+    pub board_tier: u8,
+    /// Whether the task currently has a `TaskBoard` entry
+    pub listed: bool,
+    /// Poster nonce the task's address was derived from (0 for migrated and recurring tasks)
+    pub nonce: u64,
+    /// Bounty carved into subtasks and not refunded, capped by MAX_DELEGATED_BPS
+    pub delegated: u64,
+    /// Bond the assigned agent posted at assignment, held here (on top of the bounty) until they submit
+    pub assignment_bond: u64,
+    /// A sealed-bid round is live; the task is frozen until it is awarded
+    pub bidding_open: bool,
+    /// Contributors' share of bounty refunded before settlement, paid out by claim_refund
+    pub refund_pool: u64,
+    /// Space for future fields so they can be added without another realloc
+    pub reserved: [u8; 15],
+}
+
+/// Sealed-bid auction for a task, PDA `["bidding", task]`. Closed on award.
+#[account]
+#[derive(InitSpace)]
+pub struct BiddingRound {
+    pub task_escrow: Pubkey,
+    pub poster: Pubkey,
+    /// Bounty at the time bidding opened; bids above it are invalid
+    pub max_bid: u64,
+    pub commit_deadline: i64,
+    pub reveal_deadline: i64,
+    pub bid_bond: u64,
+    pub bid_count: u32,
+    pub revealed_count: u32,
+    pub best_bidder: Option<Pubkey>,
+    pub best_amount: u64,
+    pub bump: u8,
+}
+
+/// One agent's sealed bid, PDA `["bid", task, agent]`.
+/// `commitment = sha256(amount.to_le_bytes() || salt || agent)`.
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBid {
+    pub task_escrow: Pubkey,
+    pub agent: Pubkey,
+    pub poster: Pubkey,
+    pub commitment: [u8; 32],
+    pub bond: u64,
+    pub reveal_deadline: i64,
+    pub revealed_amount: Option<u64>,
+    pub bump: u8,
+}
+
+/// Per-poster task counter, PDA `["poster_nonce", poster]`. Task `n` of a poster
+/// lives at `["task", poster, n.to_le_bytes()]`.
+#[account]
+#[derive(InitSpace)]
+pub struct PosterNonce {
+    pub poster: Pubkey,
+    pub next_task: u64,
+    pub bump: u8,
+}
+
+/// Fixed-layout page of listed tasks in one bounty tier,
+/// PDA `["board", tier, page]`. Empty slots have a default task key.
+#[account(zero_copy)]
+pub struct TaskBoard {
+    pub page: u32,
+    pub count: u32,
+    pub tier: u8,
+    pub _padding: [u8; 7],
+    pub entries: [BoardEntry; BOARD_PAGE_SIZE],
+}
+
+#[zero_copy]
+#[derive(Default)]
+pub struct BoardEntry {
+    pub task: Pubkey,
+    pub bounty_amount: u64,
+    pub required_skills: u64,
+    /// `TaskStatus` discriminant
+    pub status: u8,
+    pub _padding: [u8; 7],
+}
+
+/// `TaskEscrow` as it was laid out before versioning. Only read by `migrate_task`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct TaskEscrowV0 {
+    pub poster: Pubkey,
+    #[max_len(50)]
+    pub task_id: String,
+    #[max_len(200)]
+    pub description: String,
+    pub bounty_amount: u64,
+    pub status: TaskStatus,
+    pub assigned_agent: Option<Pubkey>,
+    pub completed_agent: Option<Pubkey>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct FundingGoal {
+    pub goal: u64,
+    pub deadline: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    pub task_escrow: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct TeamMember {
+    pub agent: Pubkey,
+    pub share_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct ContestConfig {
+    pub submission_deadline: i64,
+    #[max_len(MAX_CONTEST_WINNERS)]
+    pub payout_bps: Vec<u16>,
+    pub entry_count: u32,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecurringTask {
+    pub poster: Pubkey,
+    #[max_len(40)]
+    pub recurring_id: String,
+    #[max_len(64)]
+    pub title: String,
+    #[max_len(256)]
+    pub spec_uri: String,
+    pub spec_hash: [u8; 32],
+    /// Pre-assigned agent for every instance, if any
+    pub agent: Option<Pubkey>,
+    pub bounty_per_period: u64,
+    pub instance_rent: u64,
+    pub period_secs: i64,
+    pub periods_total: u32,
+    pub periods_opened: u32,
+    pub next_open_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct OracleConfig {
+    pub admin: Pubkey,
+    pub oracle: Pubkey,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TaskResult {
+    pub task_escrow: Pubkey,
+    pub worker: Pubkey,
+    #[max_len(256)]
+    pub result_url: String,
+    pub url_hash: [u8; 32],
+    pub status: ResultStatus,
+    pub submitted_at: i64,
+    pub oracle_nonce: u64,
+    pub bump: u8,
+    /// Bond held in this account until the verdict
+    pub bond: u64,
+    /// Set for encrypted deliveries; `url_hash` is then the ciphertext hash
+    pub encryption: Option<EncryptedDelivery>,
+    /// What `url_hash` commits to
+    pub commitment: CommitmentScheme,
+    /// sha256 of the delivered artifact, once revealed (UrlAndDigest only)
+    pub content_digest: Option<[u8; 32]>,
+    /// Exact commit delivered, for code tasks
+    pub git: Option<GitDeliverable>,
+    /// The oracle's graded verdict, once given
+    pub verdict: Option<Verdict>,
+    /// How the bounty was split, once paid
+    pub settlement: Option<Settlement>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Settlement {
+    /// Paid to the worker, or split across the team
+    pub worker_amount: u64,
+    /// Returned to the funders pro-rata (backers via `claim_refund`), or to the
+    /// parent's bounty for a subtask
+    pub poster_refund: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Verdict {
+    pub passed: bool,
+    /// Quality score, 0-100; feeds the worker's reputation
+    pub score: u8,
+    /// Share of the bounty the worker is paid; the rest is refunded to the funders
+    pub payout_bps: u16,
+    pub reason: VerdictReason,
+    /// sha256 of the oracle's full evaluation report
+    pub report_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum VerdictReason {
+    /// Full payout; the only reason allowed on a verdict paying 100%
+    Accepted,
+    /// Content at `result_url` did not match the committed hash
+    HashMismatch,
+    /// The deliverable could not be fetched
+    FetchFailed,
+    /// Parts of the spec were not delivered
+    Incomplete,
+    /// Delivered, but not what the spec asked for
+    SpecMismatch,
+    /// Meets the spec below the required quality bar
+    LowQuality,
+    /// The attached git commit, tree or pull request did not check out
+    GitMismatch,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct GitDeliverable {
+    #[max_len(MAX_REPOSITORY_LEN)]
+    pub repository: String,
+    pub commit: GitObjectId,
+    /// Root tree of `commit`, so the paid-for contents can be checked without history
+    pub tree: GitObjectId,
+    pub pull_request: Option<u32>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum GitObjectId {
+    Sha1([u8; 20]),
+    Sha256([u8; 32]),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum CommitmentScheme {
+    /// Unchecked on-chain; by convention the sha256 of the content at `result_url`
+    Opaque,
+    /// `url_hash == sha256(result_url)`, checked at submission
+    UrlHash,
+    /// `url_hash == sha256(result_url || content_digest)`, checked at reveal
+    UrlAndDigest,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct EncryptedDelivery {
+    /// `sha256` of the key blob the worker must publish to be paid
+    pub key_commitment: [u8; 32],
+    /// When the oracle passed the result
+    pub verified_at: Option<i64>,
+    #[max_len(MAX_KEY_BLOB_LEN)]
+    pub released_key: Vec<u8>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
 pub enum ResultStatus {
     Pending,
     Verified,
+    /// Left by earlier versions; failed results are now closed so the task can reopen
     Failed,
     Expired,
+    /// Passed, but the encrypted deliverable's key hasn't been released yet
+    AwaitingKey,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -383,6 +3100,7 @@ pub enum TaskStatus {
     InProgress,
     Completed,
     Cancelled,
+    Funding,
 }
 
 #[error_code]
@@ -395,10 +3113,116 @@ pub enum EscrowError {
     TaskAlreadyCompleted,
     #[msg("The provided agent does not match the assigned agent.")]
     WrongAgent,
-    #[msg("Result URL is too long (max 256 bytes).")]
+    #[msg("URL is too long (max 256 bytes).")]
     UrlTooLong,
+    #[msg("Task title is too long (max 64 bytes).")]
+    TitleTooLong,
     #[msg("Result has already been settled.")]
     AlreadySettled,
     #[msg("Oracle timeout period has not been reached yet.")]
     TimeoutNotReached,
+    #[msg("Spec hash does not match the task's committed spec.")]
+    SpecHashMismatch,
+    #[msg("This action is not available on contest tasks.")]
+    ContestTask,
+    #[msg("Task is not a contest.")]
+    NotAContest,
+    #[msg("Deadline must be in the future.")]
+    InvalidDeadline,
+    #[msg("Payout split must have 1-5 ranks totalling 10000 bps.")]
+    InvalidPayoutSplit,
+    #[msg("Contest submission window has closed.")]
+    SubmissionWindowClosed,
+    #[msg("Contest submission window is still open.")]
+    SubmissionWindowOpen,
+    #[msg("Ranked entries are invalid or out of order.")]
+    InvalidRanking,
+    #[msg("Contest already has entries and settlement has not timed out.")]
+    ContestHasEntries,
+    #[msg("Escrow holds fewer lamports than required.")]
+    InsufficientEscrow,
+    #[msg("Arithmetic overflow.")]
+    Overflow,
+    #[msg("Team must have 2-5 distinct members with non-zero shares.")]
+    InvalidTeam,
+    #[msg("Team member accounts are missing or out of order.")]
+    TeamAccountsMismatch,
+    #[msg("Amount must be non-zero and within the available bounty.")]
+    InvalidAmount,
+    #[msg("Task has subtasks that are not settled yet.")]
+    OpenSubtasks,
+    #[msg("Parent task account is missing or does not match.")]
+    ParentMismatch,
+    #[msg("Task ID is too long.")]
+    TaskIdTooLong,
+    #[msg("Recurring schedule needs a bounty, at least one period and a positive interval.")]
+    InvalidSchedule,
+    #[msg("All funded periods have already been opened.")]
+    NoPeriodsLeft,
+    #[msg("The next period is not due yet.")]
+    PeriodNotDue,
+    #[msg("The assigned agent must co-sign this change.")]
+    AgentConsentRequired,
+    #[msg("The funding deadline has passed.")]
+    FundingClosed,
+    #[msg("The funding window is still open.")]
+    FundingStillOpen,
+    #[msg("Poster profile given without the escrow authority and registry program.")]
+    MissingRegistryAccounts,
+    #[msg("Account is not in the legacy layout.")]
+    AlreadyMigrated,
+    #[msg("Task board page does not match the task or its bounty tier.")]
+    InvalidBoard,
+    #[msg("Task board page is full.")]
+    BoardPageFull,
+    #[msg("Task is already listed.")]
+    AlreadyListed,
+    #[msg("The bid commit window has closed.")]
+    CommitWindowClosed,
+    #[msg("The bid reveal window is not open.")]
+    RevealWindowNotOpen,
+    #[msg("Bid has already been revealed.")]
+    BidAlreadyRevealed,
+    #[msg("Revealed bid does not match its commitment.")]
+    BidCommitmentMismatch,
+    #[msg("Result is not an encrypted delivery.")]
+    NotEncrypted,
+    #[msg("Released key does not match its commitment.")]
+    KeyCommitmentMismatch,
+    #[msg("Key blob exceeds 128 bytes.")]
+    KeyTooLong,
+    #[msg("Result does not match its commitment.")]
+    CommitmentMismatch,
+    #[msg("The content digest must be revealed before the verdict.")]
+    DigestNotRevealed,
+    #[msg("Content digest has already been revealed.")]
+    DigestAlreadyRevealed,
+    #[msg("Repository must be 1-100 bytes and commit and tree must share a hash format.")]
+    InvalidGitDeliverable,
+    #[msg("A git deliverable is already attached to this result.")]
+    GitDeliverableAttached,
+    #[msg("Verdict must score 0-100, pay out only when passed, and use Accepted exactly when paying 100%.")]
+    InvalidVerdict,
+    #[msg("Subtasks are paid through the oracle, not by the delegating agent.")]
+    SubtaskNeedsOracle,
+    #[msg("Delegating this much would exceed the share of the bounty that may go to subtasks.")]
+    DelegationCapExceeded,
+    #[msg("Subtask has not been unsettled long enough to cancel.")]
+    SubtaskNotStale,
+    #[msg("A result has already been submitted for this task.")]
+    AlreadySubmitted,
+    #[msg("The assigned agent still has time to submit.")]
+    NotAbandoned,
+    #[msg("Bounty moved to another tier; pass a board page of the new tier.")]
+    BoardTierChanged,
+    #[msg("A sealed-bid round is live on this task.")]
+    BiddingInProgress,
+    #[msg("The key of an encrypted result must be published first.")]
+    KeyNotReleased,
+    #[msg("Key has already been released.")]
+    KeyAlreadyReleased,
+    #[msg("A submitted result is still awaiting its verdict or key release.")]
+    ResultPending,
+    #[msg("Backers are being refunded from this task; it can't be topped up.")]
+    RefundPoolOpen,
 }
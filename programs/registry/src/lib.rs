@@ -1,7 +1,29 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
 
 declare_id!("9UZ8YFVGhZ5UBx9U8g7EzFjFCyP4P1g15UBdEpT82P1M"); // Placeholder ID
 
+/// Escrow program allowed to slash stake via CPI, signing as its `escrow_authority` PDA
+pub const ESCROW_PROGRAM_ID: Pubkey = pubkey!("DKEbMD61G68RhqK37Z7Sxkf7NeuQ6WGm3q4PsA4j5kpK");
+/// Unstaked SOL stays slashable for 7 days before it can be withdrawn
+pub const UNSTAKE_COOLDOWN_SECS: i64 = 7 * 24 * 3600;
+
+// Operator key permissions (bit flags)
+pub const PERM_SUBMIT_RESULTS: u8 = 1 << 0;
+pub const PERM_UPDATE_METADATA: u8 = 1 << 1;
+pub const PERM_PLACE_BIDS: u8 = 1 << 2;
+pub const PERM_ALL: u8 = PERM_SUBMIT_RESULTS | PERM_UPDATE_METADATA | PERM_PLACE_BIDS;
+/// Session keys are ephemeral: at most 24 hours
+pub const MAX_SESSION_SECS: i64 = 24 * 3600;
+/// Handles are PDA seeds, so they must fit in a single 32-byte seed
+pub const MAX_HANDLE_LEN: usize = 32;
+pub const MIN_HANDLE_LEN: usize = 3;
+/// Current `AgentAccount` layout. Agents registered before versioning are migrated with `migrate_agent`.
+pub const AGENT_ACCOUNT_VERSION: u8 = 1;
+/// Agents per `AgentDirectory` page (48 bytes each, well under the 10KB CPI limit)
+pub const DIRECTORY_PAGE_SIZE: usize = 128;
+
 #[program]
 pub mod registry {
     use super::*;
@@ -26,7 +48,9 @@ pub mod registry {
         require!(name.len() <= 50, RegistryError::NameTooLong);
         require!(model.len() <= 30, RegistryError::ModelTooLong);
         require!(github_url.len() <= 200, RegistryError::UrlTooLong);
+        check_name_available(&name, signer.key(), &ctx.accounts.name_handle)?;
 
+        agent_account.version = AGENT_ACCOUNT_VERSION;
         agent_account.wallet = signer.key();
         agent_account.name = name;
         agent_account.model = model;
@@ -39,6 +63,7 @@ pub mod registry {
         agent_account.verified = false;
         agent_account.registered_at = clock.unix_timestamp;
         agent_account.metadata_url = String::new(); 
+        add_to_directory(&ctx.accounts.directory, agent_account)?;
 
         msg!("Agent registered: {}. Waiting for Oracle verification.", agent_account.name);
         Ok(())
@@ -48,6 +73,7 @@ pub mod registry {
         ctx: Context<VerifyAgent>,
         reputation_score: u8,
         verified_specialties: String,
+        skills: u64,
     ) -> Result<()> {
         let agent_account = &mut ctx.accounts.agent_account;
         
@@ -56,8 +82,16 @@ pub mod registry {
 
         agent_account.reputation = reputation_score;
         agent_account.specialties = verified_specialties;
+        agent_account.skills = skills;
         agent_account.verified = true;
 
+        if let Some(mut directory) = load_listed_page(&ctx.accounts.directory, agent_account)? {
+            let entry = &mut directory.entries[agent_account.directory_slot as usize];
+            entry.reputation = reputation_score;
+            entry.verified = 1;
+            entry.skills = skills;
+        }
+
         msg!("Agent {} verified by Oracle. Score: {}", agent_account.name, reputation_score);
         Ok(())
     }
@@ -67,6 +101,20 @@ pub mod registry {
         metadata_url: String,
     ) -> Result<()> {
         let agent_account = &mut ctx.accounts.agent_account;
+        match &ctx.accounts.session_key {
+            Some(session_key) => check_session_authority(
+                agent_account.wallet,
+                ctx.accounts.signer.key(),
+                session_key,
+                PERM_UPDATE_METADATA,
+            )?,
+            None => check_agent_authority(
+                agent_account.wallet,
+                ctx.accounts.signer.key(),
+                ctx.accounts.operator_key.as_deref(),
+                PERM_UPDATE_METADATA,
+            )?,
+        }
         require!(metadata_url.len() <= 200, RegistryError::UrlTooLong);
         agent_account.metadata_url = metadata_url;
         msg!("Agent updated profile for: {}", agent_account.name);
@@ -75,21 +123,40 @@ pub mod registry {
 
     /// Register agent for FREE — admin pays rent on behalf of the bot.
     /// Bot pays nothing. Zero cost to register skills on-chain.
+    /// Sponsorships are capped per epoch by the budget on RegistryConfig.
+    /// The agent wallet co-signs so nobody gets a profile they didn't agree to.
     pub fn register_agent_free(
         ctx: Context<RegisterAgentFree>,
-        agent_wallet: Pubkey,
         name: String,
         model: String,
         github_url: String,
     ) -> Result<()> {
         let agent_account = &mut ctx.accounts.agent_account;
+        let registry_config = &mut ctx.accounts.registry_config;
         let clock = Clock::get()?;
 
         require!(name.len() <= 50, RegistryError::NameTooLong);
         require!(model.len() <= 30, RegistryError::ModelTooLong);
         require!(github_url.len() <= 200, RegistryError::UrlTooLong);
+        check_name_available(&name, ctx.accounts.agent_wallet.key(), &ctx.accounts.name_handle)?;
+
+        // Counters reset at each epoch boundary
+        if registry_config.sponsored_epoch != clock.epoch {
+            registry_config.sponsored_epoch = clock.epoch;
+            registry_config.sponsored_count = 0;
+            registry_config.sponsored_lamports = 0;
+        }
+        let rent = agent_account.to_account_info().lamports();
+        require!(
+            registry_config.sponsored_count < registry_config.max_sponsored_per_epoch
+                && registry_config.sponsored_lamports + rent <= registry_config.max_sponsored_lamports_per_epoch,
+            RegistryError::SponsorshipBudgetExceeded
+        );
+        registry_config.sponsored_count += 1;
+        registry_config.sponsored_lamports += rent;
 
-        agent_account.wallet = agent_wallet;
+        agent_account.version = AGENT_ACCOUNT_VERSION;
+        agent_account.wallet = ctx.accounts.agent_wallet.key();
         agent_account.name = name;
         agent_account.model = model;
         agent_account.github_url = github_url;
@@ -100,16 +167,562 @@ pub mod registry {
         agent_account.verified = false;
         agent_account.registered_at = clock.unix_timestamp;
         agent_account.metadata_url = String::new();
+        add_to_directory(&ctx.accounts.directory, agent_account)?;
 
         msg!("Agent registered FREE: {}. Admin paid rent.", agent_account.name);
         Ok(())
     }
 
+    /// Set how many free registrations the admin will sponsor per epoch.
+    /// A zero budget disables free registration.
+    pub fn set_sponsorship_budget(
+        ctx: Context<SetSponsorshipBudget>,
+        max_sponsored_per_epoch: u32,
+        max_sponsored_lamports_per_epoch: u64,
+    ) -> Result<()> {
+        let registry_config = &mut ctx.accounts.registry_config;
+        registry_config.max_sponsored_per_epoch = max_sponsored_per_epoch;
+        registry_config.max_sponsored_lamports_per_epoch = max_sponsored_lamports_per_epoch;
+        msg!(
+            "Sponsorship budget: {} accounts / {} lamports per epoch",
+            max_sponsored_per_epoch,
+            max_sponsored_lamports_per_epoch
+        );
+        Ok(())
+    }
+
     /// Close agent account — rent returned to admin (treasury).
     pub fn close_agent(ctx: Context<CloseAgent>) -> Result<()> {
+        let agent_account = &ctx.accounts.agent_account;
+        require!(
+            agent_account.stake == 0 && agent_account.unstaking == 0,
+            RegistryError::StakeOutstanding
+        );
+        // Otherwise the wallet could re-register under another name and hold two handles
+        require!(!agent_account.handle_claimed, RegistryError::HandleAlreadyHeld);
+        if let Some(mut directory) = load_listed_page(&ctx.accounts.directory, agent_account)? {
+            directory.entries[agent_account.directory_slot as usize] = DirectoryEntry::default();
+            directory.count -= 1;
+        }
         msg!("Agent account closed. Rent returned to admin.");
         Ok(())
     }
+
+    /// Create the vault that holds all agent stake. Admin only, once.
+    pub fn initialize_stake_vault(ctx: Context<InitializeStakeVault>) -> Result<()> {
+        ctx.accounts.stake_vault.bump = ctx.bumps.stake_vault;
+        msg!("Stake vault initialized");
+        Ok(())
+    }
+
+    /// Agent locks SOL in the registry vault as a trust signal for posters.
+    pub fn stake(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        require!(amount > 0, RegistryError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.signer.to_account_info(),
+            to: ctx.accounts.stake_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+
+        let agent_account = &mut ctx.accounts.agent_account;
+        agent_account.stake = agent_account.stake.checked_add(amount).ok_or(RegistryError::Overflow)?;
+        ctx.accounts.stake_vault.total_staked += amount;
+
+        msg!("Agent {} staked {} lamports. Total stake: {}", agent_account.name, amount, agent_account.stake);
+        Ok(())
+    }
+
+    /// Start the cooldown on part of the stake. It stays slashable until withdrawn.
+    pub fn request_unstake(ctx: Context<ManageStake>, amount: u64) -> Result<()> {
+        let agent_account = &mut ctx.accounts.agent_account;
+        require!(amount > 0 && amount <= agent_account.stake, RegistryError::InvalidAmount);
+
+        agent_account.stake -= amount;
+        agent_account.unstaking += amount;
+        agent_account.unstake_available_at = Clock::get()?.unix_timestamp + UNSTAKE_COOLDOWN_SECS;
+
+        msg!("Agent {} unstaking {} lamports", agent_account.name, amount);
+        Ok(())
+    }
+
+    /// Withdraw unstaked SOL once the cooldown has passed.
+    pub fn withdraw_stake(ctx: Context<ManageStake>) -> Result<()> {
+        let agent_account = &mut ctx.accounts.agent_account;
+        let amount = agent_account.unstaking;
+        require!(amount > 0, RegistryError::InvalidAmount);
+        require!(
+            Clock::get()?.unix_timestamp >= agent_account.unstake_available_at,
+            RegistryError::CooldownActive
+        );
+
+        pay_from_vault(&mut ctx.accounts.stake_vault, &ctx.accounts.signer.to_account_info(), amount)?;
+        agent_account.unstaking = 0;
+
+        msg!("Agent {} withdrew {} lamports of stake", agent_account.name, amount);
+        Ok(())
+    }
+
+    /// Slash an agent's stake (including any still cooling down) to `recipient`.
+    /// Callable by the registry admin for arbitration outcomes, or by the escrow
+    /// program via CPI signed with its `escrow_authority` PDA.
+    pub fn slash_agent(ctx: Context<SlashAgent>, amount: u64) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == ctx.accounts.registry_config.admin || authority == escrow_authority(),
+            RegistryError::Unauthorized
+        );
+
+        let agent_account = &mut ctx.accounts.agent_account;
+        let from_stake = amount.min(agent_account.stake);
+        let from_unstaking = (amount - from_stake).min(agent_account.unstaking);
+        let slashed = from_stake + from_unstaking;
+        require!(slashed > 0, RegistryError::InvalidAmount);
+
+        pay_from_vault(&mut ctx.accounts.stake_vault, &ctx.accounts.recipient, slashed)?;
+        agent_account.stake -= from_stake;
+        agent_account.unstaking -= from_unstaking;
+
+        msg!("Agent {} slashed {} lamports", agent_account.name, slashed);
+        Ok(())
+    }
+
+    /// Create a poster profile. Stats are only ever written by the escrow program.
+    pub fn register_poster(ctx: Context<RegisterPoster>) -> Result<()> {
+        let poster_account = &mut ctx.accounts.poster_account;
+        poster_account.wallet = ctx.accounts.signer.key();
+        poster_account.registered_at = Clock::get()?.unix_timestamp;
+        poster_account.bump = ctx.bumps.poster_account;
+        msg!("Poster registered: {}", poster_account.wallet);
+        Ok(())
+    }
+
+    /// Escrow CPI: record a task lifecycle event against the poster's profile.
+    pub fn record_poster_event(ctx: Context<RecordPosterEvent>, event: PosterEvent) -> Result<()> {
+        let poster_account = &mut ctx.accounts.poster_account;
+        match event {
+            PosterEvent::TaskPosted => poster_account.tasks_posted += 1,
+            PosterEvent::TaskPaid { amount, verify_secs } => {
+                poster_account.tasks_paid += 1;
+                poster_account.total_paid = poster_account.total_paid.saturating_add(amount);
+                if let Some(secs) = verify_secs {
+                    poster_account.verified_count += 1;
+                    poster_account.total_verify_secs = poster_account.total_verify_secs.saturating_add(secs);
+                }
+            }
+            PosterEvent::TaskCancelled { after_start } => {
+                poster_account.tasks_cancelled += 1;
+                if after_start {
+                    poster_account.cancelled_after_start += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Agent's cold wallet authorizes a hot operator key for day-to-day actions.
+    /// Withdrawals and deregistration always stay with the wallet.
+    pub fn add_operator(
+        ctx: Context<AddOperator>,
+        operator: Pubkey,
+        permissions: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !PERM_ALL == 0,
+            RegistryError::InvalidPermissions
+        );
+        require!(expires_at > Clock::get()?.unix_timestamp, RegistryError::InvalidExpiry);
+
+        let operator_key = &mut ctx.accounts.operator_key;
+        operator_key.agent_wallet = ctx.accounts.signer.key();
+        operator_key.operator = operator;
+        operator_key.permissions = permissions;
+        operator_key.expires_at = expires_at;
+        operator_key.bump = ctx.bumps.operator_key;
+
+        msg!("Operator {} added for {} (permissions {:#04b})", operator, operator_key.agent_wallet, permissions);
+        Ok(())
+    }
+
+    /// Revoke an operator key. Rent goes back to the agent wallet.
+    pub fn revoke_operator(ctx: Context<RevokeOperator>) -> Result<()> {
+        msg!("Operator {} revoked", ctx.accounts.operator_key.operator);
+        Ok(())
+    }
+
+    /// Agent wallet opens a short-lived session for an ephemeral key. The session may
+    /// call the instructions in `allowed` (PERM_* flags) and commit up to `max_spend`
+    /// lamports (bonds, bid deposits) on the agent's behalf before `expires_at`.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        allowed: u8,
+        expires_at: i64,
+        max_spend: u64,
+    ) -> Result<()> {
+        require!(
+            allowed != 0 && allowed & !PERM_ALL == 0,
+            RegistryError::InvalidPermissions
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            expires_at > now && expires_at - now <= MAX_SESSION_SECS,
+            RegistryError::InvalidExpiry
+        );
+
+        let session = &mut ctx.accounts.session;
+        session.agent_wallet = ctx.accounts.signer.key();
+        session.session_key = session_key;
+        session.allowed = allowed;
+        session.expires_at = expires_at;
+        session.max_spend = max_spend;
+        session.spent = 0;
+        session.bump = ctx.bumps.session;
+
+        msg!("Session {} opened for {} until {}", session_key, session.agent_wallet, expires_at);
+        Ok(())
+    }
+
+    /// End a session early. Rent goes back to the agent wallet.
+    pub fn close_session(ctx: Context<CloseSession>) -> Result<()> {
+        msg!("Session {} closed", ctx.accounts.session.session_key);
+        Ok(())
+    }
+
+    /// Escrow CPI: charge lamports committed by a session against its spend cap.
+    pub fn record_session_spend(ctx: Context<RecordSessionSpend>, amount: u64) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        let spent = session.spent.checked_add(amount).ok_or(RegistryError::Overflow)?;
+        require!(spent <= session.max_spend, RegistryError::SpendLimitExceeded);
+        session.spent = spent;
+        Ok(())
+    }
+
+    /// Escrow CPI: fold a graded oracle verdict into the agent's reputation.
+    /// Reputation moves a fifth of the way toward `score`; passes count as completed tasks.
+    pub fn record_verdict(ctx: Context<RecordVerdict>, score: u8, passed: bool) -> Result<()> {
+        require!(score <= 100, RegistryError::InvalidScore);
+
+        let agent_account = &mut ctx.accounts.agent_account;
+        agent_account.reputation = ((agent_account.reputation as u16 * 4 + score as u16) / 5) as u8;
+        if passed {
+            agent_account.tasks_completed = agent_account.tasks_completed.saturating_add(1);
+        }
+
+        if let Some(mut directory) = load_listed_page(&ctx.accounts.directory, agent_account)? {
+            directory.entries[agent_account.directory_slot as usize].reputation = agent_account.reputation;
+        }
+
+        msg!("Verdict recorded for {}: score {}, reputation {}", agent_account.wallet, score, agent_account.reputation);
+        Ok(())
+    }
+
+    /// Claim a unique handle for the signer's agent. `handle` must already be in
+    /// normalized form (see `normalize_handle`) since it is the PDA seed.
+    pub fn claim_handle(ctx: Context<ClaimHandle>, handle: String) -> Result<()> {
+        require!(normalize_handle(&handle)? == handle, RegistryError::HandleNotNormalized);
+        let agent_account = &mut ctx.accounts.agent_account;
+        require!(!agent_account.handle_claimed, RegistryError::HandleAlreadyHeld);
+        require!(normalize_handle(&agent_account.name)? == handle, RegistryError::HandleNameMismatch);
+        agent_account.handle_claimed = true;
+
+        let handle_account = &mut ctx.accounts.handle_account;
+        handle_account.handle = handle;
+        handle_account.owner = ctx.accounts.signer.key();
+        handle_account.claimed_at = Clock::get()?.unix_timestamp;
+        handle_account.bump = ctx.bumps.handle_account;
+
+        msg!("Handle @{} claimed by {}", handle_account.handle, handle_account.owner);
+        Ok(())
+    }
+
+    /// Hand a handle to another registered agent holding none. Both wallets sign.
+    /// The two agents swap names, so the handle stays bound to the agent named
+    /// after it and the previous owner no longer answers to that name.
+    pub fn transfer_handle(ctx: Context<TransferHandle>) -> Result<()> {
+        let new_owner_agent = &mut ctx.accounts.new_owner_agent;
+        require!(!new_owner_agent.handle_claimed, RegistryError::HandleAlreadyHeld);
+        let owner_agent = &mut ctx.accounts.owner_agent;
+        std::mem::swap(&mut owner_agent.name, &mut new_owner_agent.name);
+        new_owner_agent.handle_claimed = true;
+        owner_agent.handle_claimed = false;
+
+        let handle_account = &mut ctx.accounts.handle_account;
+        handle_account.owner = ctx.accounts.new_owner.key();
+        handle_account.claimed_at = Clock::get()?.unix_timestamp;
+
+        msg!("Handle @{} transferred to {}", handle_account.handle, handle_account.owner);
+        Ok(())
+    }
+
+    /// Give up a handle so anyone can claim it. Rent goes back to the owner.
+    pub fn release_handle(ctx: Context<ReleaseHandle>) -> Result<()> {
+        ctx.accounts.owner_agent.handle_claimed = false;
+        msg!("Handle @{} released", ctx.accounts.handle_account.handle);
+        Ok(())
+    }
+
+    /// Admin records an arbitration outcome against a poster.
+    pub fn record_dispute_lost(ctx: Context<RecordDisputeLost>) -> Result<()> {
+        let poster_account = &mut ctx.accounts.poster_account;
+        poster_account.disputes_lost += 1;
+        msg!("Dispute recorded against poster {}", poster_account.wallet);
+        Ok(())
+    }
+
+    /// Create an empty directory page. Anyone may pay for one once the
+    /// previous pages fill up.
+    pub fn initialize_directory_page(ctx: Context<InitializeDirectoryPage>, page: u32) -> Result<()> {
+        let mut directory = ctx.accounts.directory.load_init()?;
+        directory.page = page;
+        msg!("Directory page {} created", page);
+        Ok(())
+    }
+
+    /// List an agent that predates the directory (e.g. a migrated account).
+    pub fn list_agent(ctx: Context<ListAgent>) -> Result<()> {
+        let agent_account = &mut ctx.accounts.agent_account;
+        require!(!agent_account.listed, RegistryError::AlreadyListed);
+        add_to_directory(&ctx.accounts.directory, agent_account)?;
+        msg!("Agent {} listed on directory page {}", agent_account.name, agent_account.directory_page);
+        Ok(())
+    }
+
+    /// Rewrite an agent registered before versioning into the current layout.
+    /// Permissionless so the admin can batch it; the payer covers the extra rent.
+    pub fn migrate_agent(ctx: Context<MigrateAgent>) -> Result<()> {
+        let agent_info = ctx.accounts.agent_account.to_account_info();
+        let old_len = 8 + AgentAccountV0::INIT_SPACE;
+        let new_len = 8 + AgentAccount::INIT_SPACE;
+
+        let legacy = {
+            let data = agent_info.try_borrow_data()?;
+            require!(data.len() == old_len, RegistryError::AlreadyMigrated);
+            require!(data[..8] == AgentAccount::DISCRIMINATOR, RegistryError::AlreadyMigrated);
+            AgentAccountV0::deserialize(&mut &data[8..])?
+        };
+
+        let agent_account = AgentAccount {
+            version: AGENT_ACCOUNT_VERSION,
+            wallet: legacy.wallet,
+            name: legacy.name,
+            model: legacy.model,
+            specialties: legacy.specialties,
+            github_url: legacy.github_url,
+            metadata_url: legacy.metadata_url,
+            tasks_completed: legacy.tasks_completed,
+            total_earned: legacy.total_earned,
+            reputation: legacy.reputation,
+            verified: legacy.verified,
+            registered_at: legacy.registered_at,
+            stake: 0,
+            unstaking: 0,
+            unstake_available_at: 0,
+            skills: 0,
+            directory_page: 0,
+            directory_slot: 0,
+            listed: false,
+            handle_claimed: false,
+            reserved: [0; 48],
+        };
+
+        let rent = Rent::get()?;
+        let extra_rent = rent.minimum_balance(new_len).saturating_sub(rent.minimum_balance(old_len));
+        if extra_rent > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: agent_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, extra_rent)?;
+        }
+        agent_info.realloc(new_len, true)?;
+        agent_account.try_serialize(&mut &mut agent_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Agent {} migrated to layout v{}", agent_account.name, AGENT_ACCOUNT_VERSION);
+        Ok(())
+    }
+
+    /// Grow a config created before sponsorship budgets into the current layout.
+    /// The budget starts at zero, so free registration stays off until the admin
+    /// calls `set_sponsorship_budget`.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.registry_config.to_account_info();
+        let old_len = 8 + 32; // discriminator + admin
+        let new_len = 8 + RegistryConfig::INIT_SPACE;
+
+        let admin = {
+            let data = config_info.try_borrow_data()?;
+            require!(data.len() == old_len, RegistryError::AlreadyMigrated);
+            require!(data[..8] == RegistryConfig::DISCRIMINATOR, RegistryError::AlreadyMigrated);
+            Pubkey::deserialize(&mut &data[8..])?
+        };
+        require!(admin == ctx.accounts.admin.key(), RegistryError::Unauthorized);
+
+        let registry_config = RegistryConfig {
+            admin,
+            max_sponsored_per_epoch: 0,
+            max_sponsored_lamports_per_epoch: 0,
+            sponsored_epoch: 0,
+            sponsored_count: 0,
+            sponsored_lamports: 0,
+        };
+
+        let rent = Rent::get()?;
+        let extra_rent = rent.minimum_balance(new_len).saturating_sub(rent.minimum_balance(old_len));
+        if extra_rent > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.admin.to_account_info(),
+                to: config_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, extra_rent)?;
+        }
+        config_info.realloc(new_len, true)?;
+        registry_config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Registry config migrated");
+        Ok(())
+    }
+}
+
+/// Checks that `signer` is the agent's wallet, or an unexpired operator of it holding
+/// `permission`. Shared with the escrow program for actions taken on an agent's behalf.
+pub fn check_agent_authority(
+    wallet: Pubkey,
+    signer: Pubkey,
+    operator_key: Option<&OperatorKey>,
+    permission: u8,
+) -> Result<()> {
+    if signer == wallet {
+        return Ok(());
+    }
+    let operator_key = operator_key.ok_or(RegistryError::Unauthorized)?;
+    require!(
+        operator_key.agent_wallet == wallet && operator_key.operator == signer,
+        RegistryError::Unauthorized
+    );
+    require!(operator_key.permissions & permission == permission, RegistryError::MissingPermission);
+    require!(
+        Clock::get()?.unix_timestamp < operator_key.expires_at,
+        RegistryError::OperatorExpired
+    );
+    Ok(())
+}
+
+/// Checks that `signer` is the ephemeral key of a live session for `wallet`
+/// that allows `permission`.
+pub fn check_session_authority(
+    wallet: Pubkey,
+    signer: Pubkey,
+    session: &SessionKey,
+    permission: u8,
+) -> Result<()> {
+    require!(
+        session.agent_wallet == wallet && session.session_key == signer,
+        RegistryError::Unauthorized
+    );
+    require!(session.allowed & permission == permission, RegistryError::MissingPermission);
+    require!(
+        Clock::get()?.unix_timestamp < session.expires_at,
+        RegistryError::SessionExpired
+    );
+    Ok(())
+}
+
+/// Puts the agent in the first free slot of `directory` and remembers where.
+fn add_to_directory(directory: &AccountLoader<AgentDirectory>, agent_account: &mut AgentAccount) -> Result<()> {
+    let mut directory = directory.load_mut()?;
+    let slot = directory
+        .entries
+        .iter()
+        .position(|entry| entry.wallet == Pubkey::default())
+        .ok_or(RegistryError::DirectoryPageFull)?;
+    directory.entries[slot] = DirectoryEntry {
+        wallet: agent_account.wallet,
+        skills: agent_account.skills,
+        reputation: agent_account.reputation,
+        verified: agent_account.verified as u8,
+        _padding: [0; 6],
+    };
+    directory.count += 1;
+
+    agent_account.directory_page = directory.page;
+    agent_account.directory_slot = slot as u16;
+    agent_account.listed = true;
+    Ok(())
+}
+
+/// The directory page holding a listed agent, checked against the slot it claims.
+/// `None` for agents that were never listed.
+fn load_listed_page<'a>(
+    directory: &'a Option<AccountLoader<AgentDirectory>>,
+    agent_account: &AgentAccount,
+) -> Result<Option<std::cell::RefMut<'a, AgentDirectory>>> {
+    if !agent_account.listed {
+        return Ok(None);
+    }
+    let directory = directory.as_ref().ok_or(RegistryError::DirectoryMismatch)?.load_mut()?;
+    require!(
+        directory.page == agent_account.directory_page
+            && directory.entries[agent_account.directory_slot as usize].wallet == agent_account.wallet,
+        RegistryError::DirectoryMismatch
+    );
+    Ok(Some(directory))
+}
+
+/// Canonical form of an agent name used for handle uniqueness: trimmed, ASCII
+/// lowercase, 3-32 characters of `a-z`, `0-9`, `_` or `-`. "Opus", " opus " and
+/// "OPUS" all map to the same handle.
+pub fn normalize_handle(name: &str) -> Result<String> {
+    let handle = name.trim().to_ascii_lowercase();
+    require!(
+        (MIN_HANDLE_LEN..=MAX_HANDLE_LEN).contains(&handle.len()),
+        RegistryError::InvalidHandle
+    );
+    require!(
+        handle.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-'),
+        RegistryError::InvalidHandle
+    );
+    Ok(handle)
+}
+
+/// A name that normalizes to a handle may only be registered by that handle's
+/// owner, or while the handle is unclaimed, so nobody can sign up as "OPUS"
+/// while @opus belongs to someone else. Names that aren't valid handles
+/// (spaces, punctuation) can never be claimed and need no check.
+fn check_name_available(name: &str, wallet: Pubkey, name_handle: &Option<UncheckedAccount>) -> Result<()> {
+    let Ok(handle) = normalize_handle(name) else {
+        return Ok(());
+    };
+    let name_handle = name_handle.as_ref().ok_or(RegistryError::NameHandleRequired)?;
+    let (expected, _) = Pubkey::find_program_address(&[b"handle", handle.as_bytes()], &crate::ID);
+    require_keys_eq!(name_handle.key(), expected, RegistryError::NameHandleRequired);
+    if name_handle.data_is_empty() {
+        return Ok(());
+    }
+    let claimed = HandleAccount::try_deserialize(&mut &name_handle.try_borrow_data()?[..])?;
+    require_keys_eq!(claimed.owner, wallet, RegistryError::HandleTaken);
+    Ok(())
+}
+
+/// Address the escrow program signs registry CPIs with.
+fn escrow_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow_authority"], &ESCROW_PROGRAM_ID).0
+}
+
+/// Moves lamports out of the program-owned stake vault.
+fn pay_from_vault(vault: &mut Account<StakeVault>, to: &AccountInfo, amount: u64) -> Result<()> {
+    vault.total_staked = vault.total_staked.checked_sub(amount).ok_or(RegistryError::InsufficientStake)?;
+    let vault_info = vault.to_account_info();
+    **vault_info.try_borrow_mut_lamports()? = vault_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(RegistryError::InsufficientStake)?;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
 }
 
 #[derive(Accounts)]
@@ -117,7 +730,7 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32, // Discriminator + Pubkey
+        space = 8 + RegistryConfig::INIT_SPACE,
         seeds = [b"config"],
         bump
     )]
@@ -137,6 +750,11 @@ pub struct RegisterAgent<'info> {
         bump
     )]
     pub agent_account: Account<'info, AgentAccount>,
+    /// Any directory page with a free slot
+    #[account(mut)]
+    pub directory: AccountLoader<'info, AgentDirectory>,
+    /// CHECK: Handle PDA for the normalized name; required when the name is a valid handle
+    pub name_handle: Option<UncheckedAccount<'info>>,
     #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -146,6 +764,9 @@ pub struct RegisterAgent<'info> {
 pub struct VerifyAgent<'info> {
     #[account(mut)]
     pub agent_account: Account<'info, AgentAccount>,
+    /// The agent's page; required when the agent is listed
+    #[account(mut)]
+    pub directory: Option<AccountLoader<'info, AgentDirectory>>,
     #[account(
         seeds = [b"config"],
         bump,
@@ -159,27 +780,141 @@ pub struct VerifyAgent<'info> {
 pub struct UpdateAgent<'info> {
     #[account(
         mut,
+        seeds = [b"agent", agent_account.wallet.as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    /// The agent wallet, or an operator holding PERM_UPDATE_METADATA
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"operator", agent_account.wallet.as_ref(), signer.key().as_ref()],
+        bump = operator_key.bump
+    )]
+    pub operator_key: Option<Account<'info, OperatorKey>>,
+    #[account(
+        seeds = [b"session", agent_account.wallet.as_ref(), signer.key().as_ref()],
+        bump = session_key.bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
         seeds = [b"agent", signer.key().as_ref()],
         bump,
         constraint = agent_account.wallet == signer.key() @ RegistryError::Unauthorized
     )]
     pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session", signer.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSession<'info> {
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"session", signer.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    /// Only the agent's wallet can close
+    #[account(mut)]
     pub signer: Signer<'info>,
 }
 
-/// Admin pays rent — bot registers for FREE
 #[derive(Accounts)]
-#[instruction(agent_wallet: Pubkey)]
+pub struct RecordSessionSpend<'info> {
+    #[account(
+        mut,
+        seeds = [b"session", session.agent_wallet.as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionKey>,
+    /// The escrow program's authority PDA
+    #[account(address = escrow_authority() @ RegistryError::Unauthorized)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordVerdict<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.wallet.as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    /// The agent's page; required when the agent is listed
+    #[account(mut)]
+    pub directory: Option<AccountLoader<'info, AgentDirectory>>,
+    /// The escrow program's authority PDA
+    #[account(address = escrow_authority() @ RegistryError::Unauthorized)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct AddOperator<'info> {
+    #[account(
+        seeds = [b"agent", signer.key().as_ref()],
+        bump,
+        constraint = agent_account.wallet == signer.key() @ RegistryError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + OperatorKey::INIT_SPACE,
+        seeds = [b"operator", signer.key().as_ref(), operator.as_ref()],
+        bump
+    )]
+    pub operator_key: Account<'info, OperatorKey>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeOperator<'info> {
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"operator", signer.key().as_ref(), operator_key.operator.as_ref()],
+        bump = operator_key.bump
+    )]
+    pub operator_key: Account<'info, OperatorKey>,
+    /// Only the agent's wallet can revoke
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// Admin pays rent — bot registers for FREE, but must co-sign
+#[derive(Accounts)]
 pub struct RegisterAgentFree<'info> {
     #[account(
         init,
         payer = admin,
         space = 8 + AgentAccount::INIT_SPACE,
-        seeds = [b"agent", agent_wallet.as_ref()],
+        seeds = [b"agent", agent_wallet.key().as_ref()],
         bump
     )]
     pub agent_account: Account<'info, AgentAccount>,
+    /// Any directory page with a free slot
+    #[account(mut)]
+    pub directory: AccountLoader<'info, AgentDirectory>,
     #[account(
+        mut,
         seeds = [b"config"],
         bump,
         has_one = admin @ RegistryError::Unauthorized
@@ -187,9 +922,25 @@ pub struct RegisterAgentFree<'info> {
     pub registry_config: Account<'info, RegistryConfig>,
     #[account(mut)]
     pub admin: Signer<'info>,
+    /// The wallet the profile is created for; signs to consent, pays nothing
+    pub agent_wallet: Signer<'info>,
+    /// CHECK: Handle PDA for the normalized name; required when the name is a valid handle
+    pub name_handle: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetSponsorshipBudget<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = admin @ RegistryError::Unauthorized
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+    pub admin: Signer<'info>,
+}
+
 /// Close agent — rent goes back to admin (treasury), not the bot
 #[derive(Accounts)]
 pub struct CloseAgent<'info> {
@@ -198,6 +949,9 @@ pub struct CloseAgent<'info> {
         close = admin,
     )]
     pub agent_account: Account<'info, AgentAccount>,
+    /// The agent's page; required when the agent is listed
+    #[account(mut)]
+    pub directory: Option<AccountLoader<'info, AgentDirectory>>,
     #[account(
         seeds = [b"config"],
         bump,
@@ -208,14 +962,227 @@ pub struct CloseAgent<'info> {
     pub admin: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeStakeVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + StakeVault::INIT_SPACE,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = admin @ RegistryError::Unauthorized
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", signer.key().as_ref()],
+        bump,
+        constraint = agent_account.wallet == signer.key() @ RegistryError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut, seeds = [b"stake_vault"], bump = stake_vault.bump)]
+    pub stake_vault: Account<'info, StakeVault>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SlashAgent<'info> {
+    #[account(mut)]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut, seeds = [b"stake_vault"], bump = stake_vault.bump)]
+    pub stake_vault: Account<'info, StakeVault>,
+    #[account(seeds = [b"config"], bump)]
+    pub registry_config: Account<'info, RegistryConfig>,
+    /// Registry admin or the escrow program's authority PDA
+    pub authority: Signer<'info>,
+    /// CHECK: Receives the slashed SOL (e.g. the wronged poster or the treasury)
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterPoster<'info> {
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + PosterAccount::INIT_SPACE,
+        seeds = [b"poster", signer.key().as_ref()],
+        bump
+    )]
+    pub poster_account: Account<'info, PosterAccount>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPosterEvent<'info> {
+    #[account(
+        mut,
+        seeds = [b"poster", poster_account.wallet.as_ref()],
+        bump = poster_account.bump
+    )]
+    pub poster_account: Account<'info, PosterAccount>,
+    /// The escrow program's authority PDA
+    #[account(address = escrow_authority() @ RegistryError::Unauthorized)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: String)]
+pub struct ClaimHandle<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", signer.key().as_ref()],
+        bump,
+        constraint = agent_account.wallet == signer.key() @ RegistryError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + HandleAccount::INIT_SPACE,
+        seeds = [b"handle", handle.as_bytes()],
+        bump
+    )]
+    pub handle_account: Account<'info, HandleAccount>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferHandle<'info> {
+    #[account(
+        mut,
+        seeds = [b"handle", handle_account.handle.as_bytes()],
+        bump = handle_account.bump,
+        constraint = handle_account.owner == owner.key() @ RegistryError::Unauthorized
+    )]
+    pub handle_account: Account<'info, HandleAccount>,
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"agent", owner.key().as_ref()], bump)]
+    pub owner_agent: Account<'info, AgentAccount>,
+    /// Receiving wallet must be a registered agent and consent
+    pub new_owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"agent", new_owner.key().as_ref()],
+        bump,
+        constraint = new_owner_agent.wallet == new_owner.key() @ RegistryError::Unauthorized
+    )]
+    pub new_owner_agent: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHandle<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"handle", handle_account.handle.as_bytes()],
+        bump = handle_account.bump,
+        constraint = handle_account.owner == owner.key() @ RegistryError::Unauthorized
+    )]
+    pub handle_account: Account<'info, HandleAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [b"agent", owner.key().as_ref()], bump)]
+    pub owner_agent: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+pub struct RecordDisputeLost<'info> {
+    #[account(mut)]
+    pub poster_account: Account<'info, PosterAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = admin @ RegistryError::Unauthorized
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+    pub admin: Signer<'info>,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct RegistryConfig {
     pub admin: Pubkey,
+    // Free-registration budget, enforced per epoch
+    pub max_sponsored_per_epoch: u32,
+    pub max_sponsored_lamports_per_epoch: u64,
+    pub sponsored_epoch: u64,
+    pub sponsored_count: u32,
+    pub sponsored_lamports: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct InitializeDirectoryPage<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<AgentDirectory>(),
+        seeds = [b"directory", page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub directory: AccountLoader<'info, AgentDirectory>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.wallet.as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub directory: AccountLoader<'info, AgentDirectory>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAgent<'info> {
+    /// CHECK: legacy layout no longer deserializes as `AgentAccount`; ownership,
+    /// discriminator and length are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub agent_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: the pre-budget layout no longer deserializes as `RegistryConfig`;
+    /// discriminator, length and admin are checked in the handler
+    #[account(mut, seeds = [b"config"], bump, owner = crate::ID)]
+    pub registry_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct AgentAccount {
+    pub version: u8,                   // 1 (see AGENT_ACCOUNT_VERSION)
     pub wallet: Pubkey,                // 32
     #[max_len(50)]
     pub name: String,                  // 4 + 50
@@ -232,6 +1199,124 @@ pub struct AgentAccount {
     pub reputation: u8,                // 1
     pub verified: bool,                // 1
     pub registered_at: i64,            // 8
+    pub stake: u64,                    // 8 (lamports in the stake vault)
+    pub unstaking: u64,                // 8 (cooling down, still slashable)
+    pub unstake_available_at: i64,     // 8
+    pub skills: u64,                   // 8 (bitmap set by the Oracle)
+    pub directory_page: u32,           // 4
+    pub directory_slot: u16,           // 2
+    pub listed: bool,                  // 1
+    pub handle_claimed: bool,          // 1 (holds the handle its name normalizes to)
+    pub reserved: [u8; 48],            // 48 (room for new fields without a realloc)
+}
+
+/// Fixed-layout page of agents for on-chain iteration, PDA `["directory", page]`.
+/// Empty slots have a default wallet; `count` is the number of used slots.
+#[account(zero_copy)]
+pub struct AgentDirectory {
+    pub page: u32,
+    pub count: u32,
+    pub entries: [DirectoryEntry; DIRECTORY_PAGE_SIZE],
+}
+
+#[zero_copy]
+#[derive(Default)]
+pub struct DirectoryEntry {
+    pub wallet: Pubkey,
+    pub skills: u64,
+    pub reputation: u8,
+    pub verified: u8,
+    pub _padding: [u8; 6],
+}
+
+/// `AgentAccount` as registered before versioning. Only read by `migrate_agent`.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct AgentAccountV0 {
+    pub wallet: Pubkey,
+    #[max_len(50)]
+    pub name: String,
+    #[max_len(30)]
+    pub model: String,
+    #[max_len(200)]
+    pub specialties: String,
+    #[max_len(200)]
+    pub github_url: String,
+    #[max_len(200)]
+    pub metadata_url: String,
+    pub tasks_completed: u64,
+    pub total_earned: u64,
+    pub reputation: u8,
+    pub verified: bool,
+    pub registered_at: i64,
+}
+
+/// Public track record of a task poster. Cancellation rate is
+/// `tasks_cancelled / tasks_posted`; average time-to-verify is
+/// `total_verify_secs / verified_count`.
+#[account]
+#[derive(InitSpace)]
+pub struct PosterAccount {
+    pub wallet: Pubkey,
+    pub tasks_posted: u64,
+    pub tasks_paid: u64,
+    pub total_paid: u64,
+    pub tasks_cancelled: u64,
+    pub cancelled_after_start: u64,    // cancelled once an agent was working
+    pub disputes_lost: u64,
+    pub verified_count: u64,
+    pub total_verify_secs: u64,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PosterEvent {
+    TaskPosted,
+    TaskPaid { amount: u64, verify_secs: Option<u64> },
+    TaskCancelled { after_start: bool },
+}
+
+/// Unique agent handle. Resolve a name by deriving
+/// `["handle", normalize_handle(name)]` and reading `owner`.
+#[account]
+#[derive(InitSpace)]
+pub struct HandleAccount {
+    #[max_len(MAX_HANDLE_LEN)]
+    pub handle: String,
+    pub owner: Pubkey,                 // agent wallet
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+/// A hot key allowed to act for an agent within `permissions` until `expires_at`
+#[account]
+#[derive(InitSpace)]
+pub struct OperatorKey {
+    pub agent_wallet: Pubkey,
+    pub operator: Pubkey,
+    pub permissions: u8,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+/// Ephemeral key that counts as the agent for `allowed` instructions, with a spend cap
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    pub agent_wallet: Pubkey,
+    pub session_key: Pubkey,
+    pub allowed: u8,
+    pub expires_at: i64,
+    pub max_spend: u64,
+    pub spent: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakeVault {
+    pub total_staked: u64,
+    pub bump: u8,
 }
 
 #[error_code]
@@ -248,4 +1333,48 @@ pub enum RegistryError {
     Unauthorized,
     #[msg("Invalid reputation score (must be 0-100)")]
     InvalidScore,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Unstake cooldown has not elapsed")]
+    CooldownActive,
+    #[msg("Stake vault has insufficient funds")]
+    InsufficientStake,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Agent still has stake in the vault")]
+    StakeOutstanding,
+    #[msg("Free registration budget for this epoch is exhausted")]
+    SponsorshipBudgetExceeded,
+    #[msg("Unknown or empty permission set")]
+    InvalidPermissions,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Operator key lacks the required permission")]
+    MissingPermission,
+    #[msg("Operator key has expired")]
+    OperatorExpired,
+    #[msg("Session key has expired")]
+    SessionExpired,
+    #[msg("Session spend limit exceeded")]
+    SpendLimitExceeded,
+    #[msg("Handle must be 3-32 characters of a-z, 0-9, _ or -")]
+    InvalidHandle,
+    #[msg("Handle must be passed in normalized form")]
+    HandleNotNormalized,
+    #[msg("Account is not in the legacy layout")]
+    AlreadyMigrated,
+    #[msg("Directory page is full")]
+    DirectoryPageFull,
+    #[msg("Directory page does not hold this agent")]
+    DirectoryMismatch,
+    #[msg("Agent is already listed")]
+    AlreadyListed,
+    #[msg("Agent already holds a handle")]
+    HandleAlreadyHeld,
+    #[msg("Handle must match the agent's registered name")]
+    HandleNameMismatch,
+    #[msg("Name is a valid handle; pass its handle PDA")]
+    NameHandleRequired,
+    #[msg("Name belongs to another agent's handle")]
+    HandleTaken,
 }
\ No newline at end of file
@@ -2,29 +2,93 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { assert } from "chai";
+import { createHash } from "crypto";
+
+const sha256 = (data: string): number[] =>
+  Array.from(createHash("sha256").update(data).digest());
 
 describe("escrow", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Escrow as Program<any>;
+  const registry = anchor.workspace.Registry as Program<any>;
+  const [escrowAuthority] = PublicKey.findProgramAddressSync([Buffer.from("escrow_authority")], program.programId);
   const poster = provider.wallet as anchor.Wallet;
   const agent = anchor.web3.Keypair.generate();
+  const oracle = anchor.web3.Keypair.generate();
+  const [oracleConfigPda] = PublicKey.findProgramAddressSync([Buffer.from("oracle_config")], program.programId);
 
   const TASK_ID = "task-001";
+  const SPEC_URI = "https://chai-sol.io/specs/task-001.md";
 
   let taskEscrowPda: PublicKey;
-  let taskEscrowBump: number;
 
-  before(async () => {
-    [taskEscrowPda, taskEscrowBump] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("task"),
-        poster.publicKey.toBuffer(),
-        Buffer.from(TASK_ID),
-      ],
+  const noncePda = (owner: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync([Buffer.from("poster_nonce"), owner.toBuffer()], program.programId)[0];
+  const posterNoncePda = noncePda(poster.publicKey);
+
+  // Task PDAs are seeded by the owner's next nonce, not the task ID
+  const nextTaskPda = async (owner: PublicKey): Promise<PublicKey> => {
+    const nonce = await program.account.posterNonce.fetchNullable(noncePda(owner));
+    const next = nonce ? nonce.nextTask : new anchor.BN(0);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("task"), owner.toBuffer(), next.toArrayLike(Buffer, "le", 8)],
       program.programId
-    );
+    )[0];
+  };
+
+  const resultPdaFor = (task: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync([Buffer.from("result"), task.toBuffer()], program.programId)[0];
+
+  // Tasks are listed on page 0 of their bounty tier's board (tiers split at 0.1, 1 and 10 SOL)
+  const boardPda = (tier: number, page = 0): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("board"), Buffer.from([tier]), new anchor.BN(page).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    )[0];
+  const boardFor = (lamports: number): PublicKey =>
+    boardPda([0.1, 1, 10].filter((limit) => lamports >= limit * LAMPORTS_PER_SOL).length);
+  const tier0 = boardPda(0);
+  const tier1 = boardPda(1);
+
+  const fundedKeypair = async (sol: number): Promise<anchor.web3.Keypair> => {
+    const kp = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(kp.publicKey, sol * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    return kp;
+  };
+
+  // A verdict paying `payoutBps` of the bounty; anything short of 100% needs a reason
+  const verdict = (payoutBps: number, reason = payoutBps === 10000 ? "accepted" : "incomplete") => ({
+    passed: payoutBps > 0,
+    score: Math.round(payoutBps / 100),
+    payoutBps,
+    reason: { [reason]: {} },
+    reportHash: sha256(`report ${payoutBps} ${reason}`),
+  });
+
+  // verify_result accounts for a solo task with no registry profiles
+  const verifyAccounts = (task: PublicKey, worker: PublicKey, taskBoard: PublicKey) => ({
+    oracle: oracle.publicKey,
+    oracleConfig: oracleConfigPda,
+    taskEscrow: task,
+    taskBoard,
+    taskResult: resultPdaFor(task),
+    worker,
+    poster: poster.publicKey,
+    parentTask: null,
+    parentBoard: null,
+    parentTierBoard: null,
+    posterProfile: PublicKey.findProgramAddressSync([Buffer.from("poster"), poster.publicKey.toBuffer()], registry.programId)[0],
+    agentAccount: null,
+    agentDirectory: null,
+    escrowAuthority: null,
+    registryProgram: null,
+  });
+
+  before(async () => {
+    taskEscrowPda = await nextTaskPda(poster.publicKey);
 
     // Airdrop to agent for account existence
     const sig = await provider.connection.requestAirdrop(
@@ -32,16 +96,25 @@ describe("escrow", () => {
       0.1 * LAMPORTS_PER_SOL
     );
     await provider.connection.confirmTransaction(sig);
+
+    for (const tier of [0, 1, 2, 3]) {
+      await program.methods
+        .initializeBoardPage(tier, 0)
+        .accounts({ taskBoard: boardPda(tier), payer: poster.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+    }
   });
 
   it("initializes a task with bounty", async () => {
     const bounty = new anchor.BN(0.5 * LAMPORTS_PER_SOL);
 
     await program.methods
-      .initializeTask(TASK_ID, bounty, "Build a Solana oracle integration")
+      .initializeTask(TASK_ID, bounty, "Build a Solana oracle integration", SPEC_URI, sha256("spec v1"), new anchor.BN(0))
       .accounts({
         poster: poster.publicKey,
+        posterNonce: posterNoncePda,
         taskEscrow: taskEscrowPda,
+        taskBoard: tier1,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -49,6 +122,8 @@ describe("escrow", () => {
     const task = await program.account.taskEscrow.fetch(taskEscrowPda);
     assert.equal(task.taskId, TASK_ID);
     assert.equal(task.bountyAmount.toNumber(), bounty.toNumber());
+    assert.equal(task.specUri, SPEC_URI);
+    assert.deepEqual(task.specHash, sha256("spec v1"));
     assert.deepEqual(task.status, { open: {} });
     assert.equal(task.poster.toBase58(), poster.publicKey.toBase58());
   });
@@ -59,6 +134,7 @@ describe("escrow", () => {
       .accounts({
         poster: poster.publicKey,
         taskEscrow: taskEscrowPda,
+        taskBoard: tier1,
       })
       .rpc();
 
@@ -76,6 +152,7 @@ describe("escrow", () => {
         poster: poster.publicKey,
         agent: agent.publicKey,
         taskEscrow: taskEscrowPda,
+        taskBoard: tier1,
       })
       .rpc();
 
@@ -89,17 +166,16 @@ describe("escrow", () => {
 
   it("allows poster to cancel an open task and get refund", async () => {
     const CANCEL_TASK_ID = "task-cancel";
-    const [cancelPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("task"), poster.publicKey.toBuffer(), Buffer.from(CANCEL_TASK_ID)],
-      program.programId
-    );
+    const cancelPda = await nextTaskPda(poster.publicKey);
 
     const bounty = new anchor.BN(0.2 * LAMPORTS_PER_SOL);
     await program.methods
-      .initializeTask(CANCEL_TASK_ID, bounty, "Task to be cancelled")
+      .initializeTask(CANCEL_TASK_ID, bounty, "Task to be cancelled", SPEC_URI, sha256("cancel"), new anchor.BN(0))
       .accounts({
         poster: poster.publicKey,
+        posterNonce: posterNoncePda,
         taskEscrow: cancelPda,
+        taskBoard: tier1,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -111,6 +187,7 @@ describe("escrow", () => {
       .accounts({
         poster: poster.publicKey,
         taskEscrow: cancelPda,
+        taskBoard: tier1,
       })
       .rpc();
 
@@ -120,25 +197,24 @@ describe("escrow", () => {
 
   it("rejects completing a task with wrong agent", async () => {
     const WRONG_TASK_ID = "task-wrong";
-    const [wrongPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("task"), poster.publicKey.toBuffer(), Buffer.from(WRONG_TASK_ID)],
-      program.programId
-    );
+    const wrongPda = await nextTaskPda(poster.publicKey);
     const wrongAgent = anchor.web3.Keypair.generate();
 
     const bounty = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
     await program.methods
-      .initializeTask(WRONG_TASK_ID, bounty, "Wrong agent test")
+      .initializeTask(WRONG_TASK_ID, bounty, "Wrong agent test", SPEC_URI, sha256("wrong"), new anchor.BN(0))
       .accounts({
         poster: poster.publicKey,
+        posterNonce: posterNoncePda,
         taskEscrow: wrongPda,
+        taskBoard: tier1,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
 
     await program.methods
       .assignAgent(agent.publicKey)
-      .accounts({ poster: poster.publicKey, taskEscrow: wrongPda })
+      .accounts({ poster: poster.publicKey, taskEscrow: wrongPda, taskBoard: tier1 })
       .rpc();
 
     try {
@@ -148,6 +224,7 @@ describe("escrow", () => {
           poster: poster.publicKey,
           agent: wrongAgent.publicKey,
           taskEscrow: wrongPda,
+          taskBoard: tier1,
         })
         .rpc();
       assert.fail("Should have thrown WrongAgent error");
@@ -155,4 +232,1288 @@ describe("escrow", () => {
       assert.include(e.message, "WrongAgent");
     }
   });
+
+  it("runs a contest and splits the bounty between the top entries", async () => {
+    const CONTEST_TASK_ID = "task-contest";
+    const contestPda = await nextTaskPda(poster.publicKey);
+    const workers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const w of workers) {
+      const sig = await provider.connection.requestAirdrop(w.publicKey, 0.1 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    const bounty = new anchor.BN(0.3 * LAMPORTS_PER_SOL);
+    await program.methods
+      .initializeTask(CONTEST_TASK_ID, bounty, "Hackathon bounty", SPEC_URI, sha256("contest"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: contestPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    await program.methods
+      .openContest(new anchor.BN(deadline), [7000, 3000])
+      .accounts({ poster: poster.publicKey, taskEscrow: contestPda })
+      .rpc();
+
+    const entries: PublicKey[] = [];
+    for (const w of workers) {
+      const [entryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("entry"), contestPda.toBuffer(), w.publicKey.toBuffer()],
+        program.programId
+      );
+      entries.push(entryPda);
+      await program.methods
+        .submitEntry(`https://example.com/${w.publicKey.toBase58()}`, sha256(w.publicKey.toBase58()))
+        .accounts({ worker: w.publicKey, taskEscrow: contestPda, entry: entryPda, systemProgram: SystemProgram.programId })
+        .signers([w])
+        .rpc();
+    }
+
+    await new Promise((r) => setTimeout(r, 4000));
+
+    // Ranking one of two entries would let the poster keep the second rank's share
+    try {
+      await program.methods
+        .settleContest()
+        .accounts({ authority: poster.publicKey, oracleConfig: null, taskEscrow: contestPda, taskBoard: tier1, poster: poster.publicKey })
+        .remainingAccounts([
+          { pubkey: entries[1], isWritable: true, isSigner: false },
+          { pubkey: workers[1].publicKey, isWritable: true, isSigner: false },
+        ])
+        .rpc();
+      assert.fail("Should have rejected a short ranking");
+    } catch (e: any) {
+      assert.include(e.message, "InvalidRanking");
+    }
+
+    const winnerBefore = await provider.connection.getBalance(workers[1].publicKey);
+    await program.methods
+      .settleContest()
+      .accounts({ authority: poster.publicKey, oracleConfig: null, taskEscrow: contestPda, taskBoard: tier1, poster: poster.publicKey })
+      .remainingAccounts([
+        { pubkey: entries[1], isWritable: true, isSigner: false },
+        { pubkey: workers[1].publicKey, isWritable: true, isSigner: false },
+        { pubkey: entries[0], isWritable: true, isSigner: false },
+        { pubkey: workers[0].publicKey, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const task = await program.account.taskEscrow.fetch(contestPda);
+    assert.deepEqual(task.status, { completed: {} });
+    assert.equal(task.completedAgent.toBase58(), workers[1].publicKey.toBase58());
+    const winnerAfter = await provider.connection.getBalance(workers[1].publicKey);
+    assert.equal(winnerAfter - winnerBefore, 0.21 * LAMPORTS_PER_SOL);
+  });
+
+  it("splits a team task's bounty by share", async () => {
+    const TEAM_TASK_ID = "task-team";
+    const teamPda = await nextTaskPda(poster.publicKey);
+    const member = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(member.publicKey, 0.1 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+
+    const bounty = new anchor.BN(0.1 * LAMPORTS_PER_SOL);
+    await program.methods
+      .initializeTask(TEAM_TASK_ID, bounty, "Team task", SPEC_URI, sha256("team"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: teamPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    await program.methods
+      .assignTeam([
+        { agent: agent.publicKey, shareBps: 6000 },
+        { agent: member.publicKey, shareBps: 4000 },
+      ])
+      .accounts({ poster: poster.publicKey, taskEscrow: teamPda, taskBoard: tier1 })
+      .rpc();
+
+    const memberBefore = await provider.connection.getBalance(member.publicKey);
+    await program.methods
+      .completeTask()
+      .accounts({ poster: poster.publicKey, agent: agent.publicKey, taskEscrow: teamPda, taskBoard: tier1 })
+      .remainingAccounts([
+        { pubkey: agent.publicKey, isWritable: true, isSigner: false },
+        { pubkey: member.publicKey, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const memberAfter = await provider.connection.getBalance(member.publicKey);
+    assert.equal(memberAfter - memberBefore, 0.04 * LAMPORTS_PER_SOL);
+  });
+
+  it("delegates a subtask and returns its funds to the parent on cancel", async () => {
+    const PARENT_TASK_ID = "task-parent";
+    const CHILD_TASK_ID = "task-child";
+    const parentPda = await nextTaskPda(poster.publicKey);
+    const childPda = await nextTaskPda(agent.publicKey);
+
+    const bounty = new anchor.BN(0.2 * LAMPORTS_PER_SOL);
+    await program.methods
+      .initializeTask(PARENT_TASK_ID, bounty, "Parent task", SPEC_URI, sha256("parent"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: parentPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .assignAgent(agent.publicKey)
+      .accounts({ poster: poster.publicKey, taskEscrow: parentPda, taskBoard: tier1 })
+      .rpc();
+
+    await program.methods
+      .delegateSubtask(CHILD_TASK_ID, new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Child task", SPEC_URI, sha256("child"), new anchor.BN(0))
+      .accounts({ agent: agent.publicKey, parentTask: parentPda, posterNonce: noncePda(agent.publicKey), taskEscrow: childPda, taskBoard: tier0, parentBoard: tier1, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+
+    let parent = await program.account.taskEscrow.fetch(parentPda);
+    assert.equal(parent.bountyAmount.toNumber(), 0.15 * LAMPORTS_PER_SOL);
+    assert.equal(parent.openSubtasks, 1);
+    const child = await program.account.taskEscrow.fetch(childPda);
+    assert.isTrue(child.listed);
+    assert.equal(child.boardTier, 0);
+    const parentEntry = (await program.account.taskBoard.fetch(tier1)).entries[parent.boardSlot];
+    assert.equal(parentEntry.bountyAmount.toNumber(), 0.15 * LAMPORTS_PER_SOL);
+
+    // At most half of the bounty may be carved out across all subtasks
+    try {
+      await program.methods
+        .delegateSubtask("task-child-2", new anchor.BN(0.06 * LAMPORTS_PER_SOL), "Greedy child", SPEC_URI, sha256("child"), new anchor.BN(0))
+        .accounts({ agent: agent.publicKey, parentTask: parentPda, posterNonce: noncePda(agent.publicKey), taskEscrow: await nextTaskPda(agent.publicKey), taskBoard: tier0, parentBoard: tier1, systemProgram: SystemProgram.programId })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have capped delegation");
+    } catch (e: any) {
+      assert.include(e.message, "DelegationCapExceeded");
+    }
+
+    // The delegating agent can't pay the child out of parent funds on its own say-so
+    try {
+      await program.methods
+        .completeTask()
+        .accounts({ poster: agent.publicKey, agent: agent.publicKey, taskEscrow: childPda, taskBoard: tier0, parentTask: parentPda })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have required the oracle");
+    } catch (e: any) {
+      assert.include(e.message, "SubtaskNeedsOracle");
+    }
+
+    // Carved-out funds can't be withdrawn to the delegating agent's wallet
+    try {
+      await program.methods
+        .reduceBounty(new anchor.BN(0.01 * LAMPORTS_PER_SOL))
+        .accounts({ poster: agent.publicKey, agent: null, taskEscrow: childPda })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have rejected reducing a subtask");
+    } catch (e: any) {
+      assert.include(e.message, "InvalidStatus");
+    }
+
+    try {
+      await program.methods
+        .cancelSubtask()
+        .accounts({ parentPoster: poster.publicKey, taskEscrow: childPda, taskBoard: tier0, parentTask: parentPda, parentBoard: tier1, delegator: agent.publicKey })
+        .rpc();
+      assert.fail("Should have rejected a fresh subtask");
+    } catch (e: any) {
+      assert.include(e.message, "SubtaskNotStale");
+    }
+
+    await program.methods
+      .cancelTask()
+      .accounts({ poster: agent.publicKey, taskEscrow: childPda, taskBoard: tier0, parentTask: parentPda, parentBoard: tier1 })
+      .signers([agent])
+      .rpc();
+
+    parent = await program.account.taskEscrow.fetch(parentPda);
+    assert.equal(parent.bountyAmount.toNumber(), bounty.toNumber());
+    assert.equal(parent.openSubtasks, 0);
+  });
+
+  it("opens funded periods of a recurring task via the crank", async () => {
+    const RECURRING_ID = "daily-triage";
+    const [recurringPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("recurring"), poster.publicKey.toBuffer(), Buffer.from(RECURRING_ID)],
+      program.programId
+    );
+    const periodSeed = Buffer.alloc(4);
+    periodSeed.writeUInt32LE(0);
+    const [instancePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("task"), recurringPda.toBuffer(), periodSeed],
+      program.programId
+    );
+
+    await program.methods
+      .initializeRecurring(
+        RECURRING_ID,
+        new anchor.BN(0.01 * LAMPORTS_PER_SOL),
+        2,
+        new anchor.BN(86400),
+        new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+        "Daily triage",
+        SPEC_URI,
+        sha256("triage"),
+        agent.publicKey
+      )
+      .accounts({ poster: poster.publicKey, recurringTask: recurringPda, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    await program.methods
+      .openPeriod()
+      .accounts({ cranker: poster.publicKey, recurringTask: recurringPda, taskEscrow: instancePda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const instance = await program.account.taskEscrow.fetch(instancePda);
+    assert.equal(instance.taskId, `${RECURRING_ID}#0`);
+    assert.deepEqual(instance.status, { inProgress: {} });
+    assert.equal(instance.assignedAgent.toBase58(), agent.publicKey.toBase58());
+
+    const recurring = await program.account.recurringTask.fetch(recurringPda);
+    assert.equal(recurring.periodsOpened, 1);
+  });
+
+  it("moves a topped-up task to its new tier and refunds contributors pro-rata on cancel", async () => {
+    const TOPUP_TASK_ID = "task-topup";
+    const topupPda = await nextTaskPda(poster.publicKey);
+    const backer = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(backer.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    const [contributionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), topupPda.toBuffer(), backer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeTask(TOPUP_TASK_ID, new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Top-up task", SPEC_URI, sha256("topup"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: topupPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // 0.35 SOL crosses into tier 1, so the entry needs a page there
+    const topUp = (tierBoard: PublicKey | null) =>
+      program.methods
+        .topUpBounty(new anchor.BN(0.3 * LAMPORTS_PER_SOL))
+        .accounts({ contributor: backer.publicKey, taskEscrow: topupPda, contribution: contributionPda, taskBoard: tier0, tierBoard, systemProgram: SystemProgram.programId })
+        .signers([backer])
+        .rpc();
+    try {
+      await topUp(null);
+      assert.fail("Should have required a page of the new tier");
+    } catch (e: any) {
+      assert.include(e.message, "BoardTierChanged");
+    }
+    await topUp(tier1);
+
+    let task = await program.account.taskEscrow.fetch(topupPda);
+    assert.equal(task.bountyAmount.toNumber(), 0.35 * LAMPORTS_PER_SOL);
+    assert.equal(task.boardTier, 1);
+    const entry = (await program.account.taskBoard.fetch(tier1)).entries[task.boardSlot];
+    assert.equal(entry.task.toBase58(), topupPda.toBase58());
+    assert.equal(entry.bountyAmount.toNumber(), 0.35 * LAMPORTS_PER_SOL);
+    const oldTier = await program.account.taskBoard.fetch(tier0);
+    assert.isFalse(oldTier.entries.some((e: any) => e.task.equals(topupPda)));
+
+    await program.methods
+      .cancelTask()
+      .accounts({ poster: poster.publicKey, taskEscrow: topupPda, taskBoard: tier1 })
+      .rpc();
+    task = await program.account.taskEscrow.fetch(topupPda);
+    assert.deepEqual(task.status, { cancelled: {} });
+    assert.isFalse(task.listed);
+
+    const backerBefore = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .claimRefund()
+      .accounts({ contributor: backer.publicKey, poster: poster.publicKey, taskEscrow: topupPda, contribution: contributionPda })
+      .signers([backer])
+      .rpc();
+    const backerAfter = await provider.connection.getBalance(backer.publicKey);
+    assert.isAtLeast(backerAfter - backerBefore, 0.3 * LAMPORTS_PER_SOL - 10000);
+
+    assert.isNull(await provider.connection.getAccountInfo(topupPda));
+  });
+
+  it("opens a crowdfunded task once backers reach the goal", async () => {
+    const CROWD_TASK_ID = "task-crowd";
+    const crowdPda = await nextTaskPda(poster.publicKey);
+    const [contributionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), crowdPda.toBuffer(), poster.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeCrowdfundedTask(
+        CROWD_TASK_ID,
+        new anchor.BN(0.2 * LAMPORTS_PER_SOL),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        "Shared infra",
+        SPEC_URI,
+        sha256("crowd"),
+        new anchor.BN(0)
+      )
+      .accounts({ poster: poster.publicKey, taskEscrow: crowdPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    let task = await program.account.taskEscrow.fetch(crowdPda);
+    assert.deepEqual(task.status, { funding: {} });
+    assert.isTrue(task.listed);
+
+    await program.methods
+      .topUpBounty(new anchor.BN(0.2 * LAMPORTS_PER_SOL))
+      .accounts({ contributor: poster.publicKey, taskEscrow: crowdPda, contribution: contributionPda, taskBoard: tier0, tierBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    task = await program.account.taskEscrow.fetch(crowdPda);
+    assert.deepEqual(task.status, { open: {} });
+    assert.equal(task.contributorCount, 1);
+    assert.equal(task.boardTier, 1);
+    assert.equal((await program.account.taskBoard.fetch(tier1)).entries[task.boardSlot].status, 0); // Open
+  });
+
+  it("lists a task on its bounty tier's board at creation and drops it on completion", async () => {
+    const BOARD_TASK_ID = "task-board";
+    const boardTaskPda = await nextTaskPda(poster.publicKey);
+    const countBefore = (await program.account.taskBoard.fetch(tier1)).count;
+
+    // 0.5 SOL falls in tier 1 (0.1 - 1 SOL); a tier 0 page is refused
+    const init = (taskBoard: PublicKey) =>
+      program.methods
+        .initializeTask(BOARD_TASK_ID, new anchor.BN(0.5 * LAMPORTS_PER_SOL), "Indexed task", SPEC_URI, sha256("board"), new anchor.BN(0b11))
+        .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: boardTaskPda, taskBoard, systemProgram: SystemProgram.programId })
+        .rpc();
+    try {
+      await init(tier0);
+      assert.fail("Should have rejected a page of the wrong tier");
+    } catch (e: any) {
+      assert.include(e.message, "InvalidBoard");
+    }
+    await init(tier1);
+
+    const task = await program.account.taskEscrow.fetch(boardTaskPda);
+    assert.isTrue(task.listed);
+    const slot = task.boardSlot;
+    let board = await program.account.taskBoard.fetch(tier1);
+    assert.equal(board.count, countBefore + 1);
+    assert.equal(board.entries[slot].task.toBase58(), boardTaskPda.toBase58());
+    assert.equal(board.entries[slot].requiredSkills.toNumber(), 0b11);
+
+    await program.methods
+      .assignAgent(agent.publicKey)
+      .accounts({ poster: poster.publicKey, taskEscrow: boardTaskPda, taskBoard: tier1 })
+      .rpc();
+    board = await program.account.taskBoard.fetch(tier1);
+    assert.equal(board.entries[slot].status, 1); // InProgress
+
+    await program.methods
+      .completeTask()
+      .accounts({ poster: poster.publicKey, agent: agent.publicKey, taskEscrow: boardTaskPda, taskBoard: tier1 })
+      .rpc();
+    board = await program.account.taskBoard.fetch(tier1);
+    assert.equal(board.count, countBefore);
+    assert.equal(board.entries[slot].task.toBase58(), PublicKey.default.toBase58());
+  });
+
+  it("freezes a task during sealed bidding and refunds the unspent ceiling pro-rata", async () => {
+    const bidTaskPda = await nextTaskPda(poster.publicKey);
+    const [roundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bidding"), bidTaskPda.toBuffer()],
+      program.programId
+    );
+    const [bidPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bid"), bidTaskPda.toBuffer(), agent.publicKey.toBuffer()],
+      program.programId
+    );
+    const backer = await fundedKeypair(1);
+    const contributionPda = (who: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("contribution"), bidTaskPda.toBuffer(), who.toBuffer()],
+        program.programId
+      )[0];
+    const topUp = (who: anchor.web3.Keypair, lamports: number) =>
+      program.methods
+        .topUpBounty(new anchor.BN(lamports))
+        .accounts({ contributor: who.publicKey, taskEscrow: bidTaskPda, contribution: contributionPda(who.publicKey), taskBoard: tier0, systemProgram: SystemProgram.programId })
+        .signers([who])
+        .rpc();
+
+    // 0.05 SOL from the poster and 0.04 from a backer: a 0.09 SOL ceiling
+    await program.methods
+      .initializeTask("task-bid", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Sealed bid task", SPEC_URI, sha256("bid"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: bidTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+    await topUp(backer, 0.04 * LAMPORTS_PER_SOL);
+
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .openBidding(new anchor.BN(now + 4), new anchor.BN(now + 7), new anchor.BN(1_000_000))
+      .accounts({ poster: poster.publicKey, taskEscrow: bidTaskPda, biddingRound: roundPda, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // Nothing may move the bounty or the task while bids are sealed
+    const frozen = [
+      () => topUp(backer, 0.01 * LAMPORTS_PER_SOL),
+      () =>
+        program.methods
+          .reduceBounty(new anchor.BN(0.01 * LAMPORTS_PER_SOL))
+          .accounts({ poster: poster.publicKey, agent: null, taskEscrow: bidTaskPda, taskBoard: tier0 })
+          .rpc(),
+      () =>
+        program.methods
+          .assignAgent(agent.publicKey)
+          .accounts({ poster: poster.publicKey, taskEscrow: bidTaskPda, taskBoard: tier0 })
+          .rpc(),
+      () =>
+        program.methods
+          .cancelTask()
+          .accounts({ poster: poster.publicKey, taskEscrow: bidTaskPda, taskBoard: tier0 })
+          .rpc(),
+      () =>
+        program.methods
+          .submitResult("https://example.com/early", sha256("early"))
+          .accounts({ worker: agent.publicKey, taskEscrow: bidTaskPda, taskBoard: tier0, taskResult: resultPdaFor(bidTaskPda), systemProgram: SystemProgram.programId })
+          .signers([agent])
+          .rpc(),
+    ];
+    for (const attempt of frozen) {
+      try {
+        await attempt();
+        assert.fail("Should have been frozen by the bidding round");
+      } catch (e: any) {
+        assert.include(e.message, "BiddingInProgress");
+      }
+    }
+
+    const amount = new anchor.BN(0.03 * LAMPORTS_PER_SOL);
+    const salt = Array.from(anchor.web3.Keypair.generate().publicKey.toBytes());
+    const commitment = Array.from(
+      createHash("sha256")
+        .update(Buffer.concat([amount.toArrayLike(Buffer, "le", 8), Buffer.from(salt), agent.publicKey.toBuffer()]))
+        .digest()
+    );
+    await program.methods
+      .commitBid(agent.publicKey, commitment)
+      .accounts({ bidder: agent.publicKey, biddingRound: roundPda, sealedBid: bidPda, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 5000));
+    await program.methods
+      .revealBid(amount, salt)
+      .accounts({ bidder: agent.publicKey, biddingRound: roundPda, sealedBid: bidPda, agent: agent.publicKey })
+      .signers([agent])
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 3000));
+    const posterBefore = await provider.connection.getBalance(poster.publicKey);
+    await program.methods
+      .awardBid()
+      .accounts({ poster: poster.publicKey, taskEscrow: bidTaskPda, taskBoard: tier0, biddingRound: roundPda })
+      .rpc();
+    const posterAfter = await provider.connection.getBalance(poster.publicKey);
+
+    // 0.06 SOL excess: 5/9 to the poster now, 4/9 held for the backer
+    const excess = 0.06 * LAMPORTS_PER_SOL;
+    const posterShare = Math.floor((excess * 5) / 9);
+    const task = await program.account.taskEscrow.fetch(bidTaskPda);
+    assert.deepEqual(task.status, { inProgress: {} });
+    assert.isFalse(task.biddingOpen);
+    assert.equal(task.assignedAgent.toBase58(), agent.publicKey.toBase58());
+    assert.equal(task.bountyAmount.toNumber(), amount.toNumber());
+    assert.equal(task.refundPool.toNumber(), excess - posterShare);
+    assert.isAtLeast(posterAfter - posterBefore, posterShare); // plus the round's rent
+
+    // A backer arriving now would take part of the excess the first backer funded
+    const late = await fundedKeypair(1);
+    try {
+      await topUp(late, 0.01 * LAMPORTS_PER_SOL);
+      assert.fail("Should have refused a top-up with a refund pool open");
+    } catch (e: any) {
+      assert.include(e.message, "RefundPoolOpen");
+    }
+
+    await program.methods
+      .completeTask()
+      .accounts({ poster: poster.publicKey, agent: agent.publicKey, taskEscrow: bidTaskPda, taskBoard: tier0 })
+      .rpc();
+    const backerBefore = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .claimRefund()
+      .accounts({ contributor: backer.publicKey, poster: poster.publicKey, taskEscrow: bidTaskPda, contribution: contributionPda(backer.publicKey) })
+      .signers([backer])
+      .rpc();
+    const backerAfter = await provider.connection.getBalance(backer.publicKey);
+    assert.isAtLeast(backerAfter - backerBefore, excess - posterShare);
+  });
+
+  it("records an encrypted delivery's key commitment", async () => {
+    const encTaskPda = await nextTaskPda(poster.publicKey);
+    const [resultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("result"), encTaskPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeTask("task-encrypted", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Private deliverable", SPEC_URI, sha256("enc"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: encTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const keyBlob = Buffer.from(anchor.web3.Keypair.generate().secretKey.slice(0, 32));
+    const keyCommitment = Array.from(createHash("sha256").update(keyBlob).digest());
+    await program.methods
+      .submitEncryptedResult("https://example.com/deliverable.enc", sha256("ciphertext"), keyCommitment)
+      .accounts({ worker: agent.publicKey, taskEscrow: encTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+
+    const result = await program.account.taskResult.fetch(resultPda);
+    assert.deepEqual(result.status, { pending: {} });
+    assert.deepEqual(result.encryption.keyCommitment, keyCommitment);
+    assert.equal(result.encryption.releasedKey.length, 0);
+  });
+
+  it("rejects a UrlHash commitment that doesn't hash the URL", async () => {
+    const commitTaskPda = await nextTaskPda(poster.publicKey);
+    const [resultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("result"), commitTaskPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeTask("task-commit", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Committed result", SPEC_URI, sha256("commit"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: commitTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const url = "https://example.com/result.json";
+    try {
+      await program.methods
+        .submitCommittedResult(url, sha256("something else"), { urlHash: {} })
+        .accounts({ worker: agent.publicKey, taskEscrow: commitTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have thrown CommitmentMismatch");
+    } catch (e: any) {
+      assert.include(e.message, "CommitmentMismatch");
+    }
+
+    await program.methods
+      .submitCommittedResult(url, sha256(url), { urlHash: {} })
+      .accounts({ worker: agent.publicKey, taskEscrow: commitTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+    const result = await program.account.taskResult.fetch(resultPda);
+    assert.deepEqual(result.commitment, { urlHash: {} });
+  });
+
+  it("opens a UrlAndDigest commitment before the result can be paid", async () => {
+    const digestTaskPda = await nextTaskPda(poster.publicKey);
+    const resultPda = resultPdaFor(digestTaskPda);
+    await program.methods
+      .initializeTask("task-digest", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Digest-committed result", SPEC_URI, sha256("digest"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: digestTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const url = "https://example.com/artifact.tar";
+    const digest = sha256("artifact contents");
+    const urlHash = Array.from(createHash("sha256").update(url).update(Buffer.from(digest)).digest());
+    await program.methods
+      .submitCommittedResult(url, urlHash, { urlAndDigest: {} })
+      .accounts({ worker: agent.publicKey, taskEscrow: digestTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+
+    // Until the digest is revealed the timeout can't pay out an artifact nobody can name
+    try {
+      await program.methods
+        .reclaimTimeout()
+        .accounts({ worker: agent.publicKey, taskEscrow: digestTaskPda, taskBoard: tier0, taskResult: resultPda, parentTask: null })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have required the digest");
+    } catch (e: any) {
+      assert.include(e.message, "DigestNotRevealed");
+    }
+
+    try {
+      await program.methods
+        .revealContentDigest(sha256("other contents"))
+        .accounts({ worker: agent.publicKey, taskResult: resultPda })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have thrown CommitmentMismatch");
+    } catch (e: any) {
+      assert.include(e.message, "CommitmentMismatch");
+    }
+
+    await program.methods
+      .revealContentDigest(digest)
+      .accounts({ worker: agent.publicKey, taskResult: resultPda })
+      .signers([agent])
+      .rpc();
+    const result = await program.account.taskResult.fetch(resultPda);
+    assert.deepEqual(result.contentDigest, digest);
+
+    try {
+      await program.methods
+        .revealContentDigest(digest)
+        .accounts({ worker: agent.publicKey, taskResult: resultPda })
+        .signers([agent])
+        .rpc();
+      assert.fail("Should have thrown DigestAlreadyRevealed");
+    } catch (e: any) {
+      assert.include(e.message, "DigestAlreadyRevealed");
+    }
+  });
+
+  it("pins a result to an exact git commit", async () => {
+    const gitTaskPda = await nextTaskPda(poster.publicKey);
+    const [resultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("result"), gitTaskPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeTask("task-git", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Code task", SPEC_URI, sha256("git"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: gitTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .submitResult("https://github.com/ladymillard/chai-sol/pull/42", sha256("pr"))
+      .accounts({ worker: agent.publicKey, taskEscrow: gitTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([agent])
+      .rpc();
+
+    const commit = Array.from(createHash("sha1").update("commit").digest());
+    const tree = Array.from(createHash("sha1").update("tree").digest());
+    await program.methods
+      .attachGitDeliverable("github.com/ladymillard/chai-sol", { sha1: [commit] }, { sha1: [tree] }, 42)
+      .accounts({ worker: agent.publicKey, taskResult: resultPda })
+      .signers([agent])
+      .rpc();
+
+    const result = await program.account.taskResult.fetch(resultPda);
+    assert.equal(result.git.repository, "github.com/ladymillard/chai-sol");
+    assert.deepEqual(result.git.commit.sha1[0], commit);
+    assert.equal(result.git.pullRequest, 42);
+  });
+
+  it("initializes the oracle", async () => {
+    await program.methods
+      .initializeOracle(oracle.publicKey)
+      .accounts({ oracleConfig: oracleConfigPda, admin: poster.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const config = await program.account.oracleConfig.fetch(oracleConfigPda);
+    assert.equal(config.oracle.toBase58(), oracle.publicKey.toBase58());
+  });
+
+  it("takes the worker bond at assignment and returns it with the bounty on a pass", async () => {
+    const worker = await fundedKeypair(1);
+    const bondTaskPda = await nextTaskPda(poster.publicKey);
+    const bounty = 0.1 * LAMPORTS_PER_SOL;
+    const bond = 0.02 * LAMPORTS_PER_SOL;
+    await program.methods
+      .initializeTask("task-bonded", new anchor.BN(bounty), "Bonded task", SPEC_URI, sha256("bonded"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: bondTaskPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .setWorkerBond(new anchor.BN(bond))
+      .accounts({ poster: poster.publicKey, taskEscrow: bondTaskPda })
+      .rpc();
+
+    // The agent posts the bond, so assigning without their signature fails
+    try {
+      await program.methods
+        .assignAgent(worker.publicKey)
+        .accounts({ poster: poster.publicKey, taskEscrow: bondTaskPda, taskBoard: tier1 })
+        .rpc();
+      assert.fail("Should have required the agent's signature");
+    } catch (e: any) {
+      assert.include(e.message, "AgentConsentRequired");
+    }
+
+    await program.methods
+      .assignAgent(worker.publicKey)
+      .accounts({ poster: poster.publicKey, agent: worker.publicKey, taskEscrow: bondTaskPda, taskBoard: tier1 })
+      .signers([worker])
+      .rpc();
+    let task = await program.account.taskEscrow.fetch(bondTaskPda);
+    assert.equal(task.assignmentBond.toNumber(), bond);
+
+    await program.methods
+      .submitResult("https://example.com/bonded", sha256("bonded result"))
+      .accounts({ worker: worker.publicKey, taskEscrow: bondTaskPda, taskBoard: tier1, taskResult: resultPdaFor(bondTaskPda), systemProgram: SystemProgram.programId })
+      .signers([worker])
+      .rpc();
+    task = await program.account.taskEscrow.fetch(bondTaskPda);
+    assert.equal(task.assignmentBond.toNumber(), 0);
+    assert.equal((await program.account.taskResult.fetch(resultPdaFor(bondTaskPda))).bond.toNumber(), bond);
+
+    const before = await provider.connection.getBalance(worker.publicKey);
+    await program.methods
+      .verifyResult(verdict(10000), sha256("bonded"))
+      .accounts(verifyAccounts(bondTaskPda, worker.publicKey, tier1))
+      .signers([oracle])
+      .rpc();
+
+    const after = await provider.connection.getBalance(worker.publicKey);
+    assert.equal(after - before, bounty + bond);
+  });
+
+  it("slashes the worker bond to the poster on a failed verdict", async () => {
+    const worker = await fundedKeypair(1);
+    const failTaskPda = await nextTaskPda(poster.publicKey);
+    const resultPda = resultPdaFor(failTaskPda);
+    await program.methods
+      .initializeTask("task-bond-fail", new anchor.BN(0.1 * LAMPORTS_PER_SOL), "Bonded task", SPEC_URI, sha256("bond-fail"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: failTaskPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .setWorkerBond(new anchor.BN(0.02 * LAMPORTS_PER_SOL))
+      .accounts({ poster: poster.publicKey, taskEscrow: failTaskPda })
+      .rpc();
+
+    // Nobody was assigned, so the worker posts the bond when submitting
+    await program.methods
+      .submitResult("https://example.com/junk", sha256("junk"))
+      .accounts({ worker: worker.publicKey, taskEscrow: failTaskPda, taskBoard: tier1, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([worker])
+      .rpc();
+
+    await program.methods
+      .verifyResult(verdict(0, "specMismatch"), sha256("bond-fail"))
+      .accounts(verifyAccounts(failTaskPda, worker.publicKey, tier1))
+      .signers([oracle])
+      .rpc();
+
+    // The bond went to the poster and the result closed, so the task is open to anyone again
+    assert.isNull(await provider.connection.getAccountInfo(resultPda));
+    const task = await program.account.taskEscrow.fetch(failTaskPda);
+    assert.deepEqual(task.status, { open: {} });
+    assert.isNull(task.assignedAgent);
+    assert.isNull(task.startedAt);
+
+    const next = await fundedKeypair(1);
+    await program.methods
+      .submitResult("https://example.com/retry", sha256("retry"))
+      .accounts({ worker: next.publicKey, taskEscrow: failTaskPda, taskBoard: tier1, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([next])
+      .rpc();
+    const retry = await program.account.taskResult.fetch(resultPda);
+    assert.equal(retry.worker.toBase58(), next.publicKey.toBase58());
+  });
+
+  it("refuses to cancel under a pending result and returns its bond when the poster completes", async () => {
+    const worker = await fundedKeypair(1);
+    const pendingTaskPda = await nextTaskPda(poster.publicKey);
+    const resultPda = resultPdaFor(pendingTaskPda);
+    const bounty = 0.05 * LAMPORTS_PER_SOL;
+    const bond = 0.01 * LAMPORTS_PER_SOL;
+    await program.methods
+      .initializeTask("task-pending", new anchor.BN(bounty), "Submitted task", SPEC_URI, sha256("pending"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: pendingTaskPda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .setWorkerBond(new anchor.BN(bond))
+      .accounts({ poster: poster.publicKey, taskEscrow: pendingTaskPda })
+      .rpc();
+    await program.methods
+      .submitResult("https://example.com/pending", sha256("pending result"))
+      .accounts({ worker: worker.publicKey, taskEscrow: pendingTaskPda, taskBoard: tier0, taskResult: resultPda, systemProgram: SystemProgram.programId })
+      .signers([worker])
+      .rpc();
+
+    // The poster has seen result_url; walking away now would keep the work and the bond
+    try {
+      await program.methods
+        .cancelTask()
+        .accounts({ poster: poster.publicKey, taskEscrow: pendingTaskPda, taskBoard: tier0, taskResult: resultPda })
+        .rpc();
+      assert.fail("Should have refused to cancel");
+    } catch (e: any) {
+      assert.include(e.message, "ResultPending");
+    }
+
+    const resultInfo = await provider.connection.getAccountInfo(resultPda);
+    const before = await provider.connection.getBalance(worker.publicKey);
+    await program.methods
+      .completeTask()
+      .accounts({ poster: poster.publicKey, agent: worker.publicKey, taskEscrow: pendingTaskPda, taskBoard: tier0, taskResult: resultPda })
+      .rpc();
+    const after = await provider.connection.getBalance(worker.publicKey);
+    assert.equal(after - before, bounty + resultInfo.lamports, "bounty plus the result's bond and rent");
+    assert.isAbove(resultInfo.lamports, bond);
+    assert.isNull(await provider.connection.getAccountInfo(resultPda));
+  });
+
+  it("pays an encrypted result only once its key is released", async () => {
+    const worker = await fundedKeypair(1);
+    const bounty = 0.05 * LAMPORTS_PER_SOL;
+    const encryptedTask = async (taskId: string, keyBlob: Buffer): Promise<PublicKey> => {
+      const pda = await nextTaskPda(poster.publicKey);
+      await program.methods
+        .initializeTask(taskId, new anchor.BN(bounty), "Private deliverable", SPEC_URI, sha256(taskId), new anchor.BN(0))
+        .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: pda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+        .rpc();
+      await program.methods
+        .submitEncryptedResult("https://example.com/deliverable.enc", sha256(`${taskId} ciphertext`), Array.from(createHash("sha256").update(keyBlob).digest()))
+        .accounts({ worker: worker.publicKey, taskEscrow: pda, taskBoard: tier0, taskResult: resultPdaFor(pda), systemProgram: SystemProgram.programId })
+        .signers([worker])
+        .rpc();
+      return pda;
+    };
+    const releaseKey = (task: PublicKey, keyBlob: Buffer) =>
+      program.methods
+        .releaseKey(keyBlob)
+        .accounts({
+          worker: worker.publicKey,
+          taskEscrow: task,
+          taskBoard: tier0,
+          taskResult: resultPdaFor(task),
+          poster: poster.publicKey,
+          parentTask: null,
+          parentBoard: null,
+          parentTierBoard: null,
+          posterProfile: verifyAccounts(task, worker.publicKey, tier0).posterProfile,
+          escrowAuthority: null,
+          registryProgram: null,
+        })
+        .signers([worker])
+        .rpc();
+
+    const keyBlob = Buffer.from(anchor.web3.Keypair.generate().secretKey.slice(0, 32));
+    const task = await encryptedTask("task-key-release", keyBlob);
+
+    // The poster can't open the deliverable yet, so the oracle timeout can't pay it
+    try {
+      await program.methods
+        .reclaimTimeout()
+        .accounts({ worker: worker.publicKey, taskEscrow: task, taskBoard: tier0, taskResult: resultPdaFor(task), parentTask: null })
+        .signers([worker])
+        .rpc();
+      assert.fail("Should have required the key");
+    } catch (e: any) {
+      assert.include(e.message, "KeyNotReleased");
+    }
+
+    await program.methods
+      .verifyResult(verdict(10000), sha256("task-key-release"))
+      .accounts(verifyAccounts(task, worker.publicKey, tier0))
+      .signers([oracle])
+      .rpc();
+    let result = await program.account.taskResult.fetch(resultPdaFor(task));
+    assert.deepEqual(result.status, { awaitingKey: {} });
+    assert.isNotNull(result.encryption.verifiedAt);
+
+    try {
+      await program.methods
+        .reclaimUnreleasedKey()
+        .accounts({ poster: poster.publicKey, taskEscrow: task, taskBoard: tier0, taskResult: resultPdaFor(task), worker: worker.publicKey })
+        .rpc();
+      assert.fail("Should have waited out the key release timeout");
+    } catch (e: any) {
+      assert.include(e.message, "TimeoutNotReached");
+    }
+
+    try {
+      await releaseKey(task, Buffer.from("not the committed key"));
+      assert.fail("Should have checked the key commitment");
+    } catch (e: any) {
+      assert.include(e.message, "KeyCommitmentMismatch");
+    }
+
+    const before = await provider.connection.getBalance(worker.publicKey);
+    await releaseKey(task, keyBlob);
+    const after = await provider.connection.getBalance(worker.publicKey);
+    assert.equal(after - before, bounty);
+    result = await program.account.taskResult.fetch(resultPdaFor(task));
+    assert.deepEqual(result.status, { verified: {} });
+    assert.deepEqual(Buffer.from(result.encryption.releasedKey), keyBlob);
+
+    // A key published ahead of the verdict is stored, and the pass then pays directly
+    const earlyBlob = Buffer.from(anchor.web3.Keypair.generate().secretKey.slice(0, 32));
+    const early = await encryptedTask("task-key-early", earlyBlob);
+    await releaseKey(early, earlyBlob);
+    result = await program.account.taskResult.fetch(resultPdaFor(early));
+    assert.deepEqual(result.status, { pending: {} });
+    assert.deepEqual(Buffer.from(result.encryption.releasedKey), earlyBlob);
+
+    const earlyBefore = await provider.connection.getBalance(worker.publicKey);
+    await program.methods
+      .verifyResult(verdict(10000), sha256("task-key-early"))
+      .accounts(verifyAccounts(early, worker.publicKey, tier0))
+      .signers([oracle])
+      .rpc();
+    assert.equal((await provider.connection.getBalance(worker.publicKey)) - earlyBefore, bounty);
+    result = await program.account.taskResult.fetch(resultPdaFor(early));
+    assert.deepEqual(result.status, { verified: {} });
+  });
+
+  it("refunds the unpaid share of a partial payout to the poster and backers pro-rata", async () => {
+    const worker = await fundedKeypair(1);
+    const backer = await fundedKeypair(1);
+    const partialTaskPda = await nextTaskPda(poster.publicKey);
+    const [contributionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("contribution"), partialTaskPda.toBuffer(), backer.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeTask("task-partial", new anchor.BN(0.1 * LAMPORTS_PER_SOL), "Partially delivered task", SPEC_URI, sha256("partial"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: partialTaskPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+    // The backer funds half of the 0.2 SOL bounty
+    await program.methods
+      .topUpBounty(new anchor.BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({ contributor: backer.publicKey, taskEscrow: partialTaskPda, contribution: contributionPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .signers([backer])
+      .rpc();
+    await program.methods
+      .submitResult("https://example.com/partial", sha256("partial result"))
+      .accounts({ worker: worker.publicKey, taskEscrow: partialTaskPda, taskBoard: tier1, taskResult: resultPdaFor(partialTaskPda), systemProgram: SystemProgram.programId })
+      .signers([worker])
+      .rpc();
+
+    const workerBefore = await provider.connection.getBalance(worker.publicKey);
+    const posterBefore = await provider.connection.getBalance(poster.publicKey);
+    await program.methods
+      .verifyResult(verdict(5000), sha256("partial"))
+      .accounts(verifyAccounts(partialTaskPda, worker.publicKey, tier1))
+      .signers([oracle])
+      .rpc();
+    const workerAfter = await provider.connection.getBalance(worker.publicKey);
+    const posterAfter = await provider.connection.getBalance(poster.publicKey);
+
+    // Half the bounty to the worker; the other 0.1 SOL splits evenly between the funders
+    assert.equal(workerAfter - workerBefore, 0.1 * LAMPORTS_PER_SOL);
+    assert.isAtLeast(posterAfter - posterBefore, 0.05 * LAMPORTS_PER_SOL - 10000, "poster pays the fee");
+    const result = await program.account.taskResult.fetch(resultPdaFor(partialTaskPda));
+    assert.equal(result.settlement.workerAmount.toNumber(), 0.1 * LAMPORTS_PER_SOL);
+    assert.equal(result.settlement.posterRefund.toNumber(), 0.1 * LAMPORTS_PER_SOL);
+    const task = await program.account.taskEscrow.fetch(partialTaskPda);
+    assert.deepEqual(task.status, { completed: {} });
+    assert.equal(task.refundPool.toNumber(), 0.05 * LAMPORTS_PER_SOL);
+
+    const backerBefore = await provider.connection.getBalance(backer.publicKey);
+    await program.methods
+      .claimRefund()
+      .accounts({ contributor: backer.publicKey, poster: poster.publicKey, taskEscrow: partialTaskPda, contribution: contributionPda })
+      .signers([backer])
+      .rpc();
+    const backerAfter = await provider.connection.getBalance(backer.publicKey);
+    assert.isAtLeast(backerAfter - backerBefore, 0.05 * LAMPORTS_PER_SOL - 10000);
+  });
+
+  it("holds an assignment bond against abandonment and refunds it on cancel", async () => {
+    const worker = await fundedKeypair(1);
+    const idleTaskPda = await nextTaskPda(poster.publicKey);
+    const bond = 0.02 * LAMPORTS_PER_SOL;
+    await program.methods
+      .initializeTask("task-bond-idle", new anchor.BN(0.1 * LAMPORTS_PER_SOL), "Bonded task", SPEC_URI, sha256("idle"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: idleTaskPda, taskBoard: tier1, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .setWorkerBond(new anchor.BN(bond))
+      .accounts({ poster: poster.publicKey, taskEscrow: idleTaskPda })
+      .rpc();
+    await program.methods
+      .assignAgent(worker.publicKey)
+      .accounts({ poster: poster.publicKey, agent: worker.publicKey, taskEscrow: idleTaskPda, taskBoard: tier1 })
+      .signers([worker])
+      .rpc();
+
+    // The agent has ABANDON_TIMEOUT_SECS before the poster may reclaim and slash
+    try {
+      await program.methods
+        .reclaimAbandoned()
+        .accounts({ poster: poster.publicKey, taskEscrow: idleTaskPda, taskBoard: tier1, taskResult: resultPdaFor(idleTaskPda) })
+        .rpc();
+      assert.fail("Should have rejected an early reclaim");
+    } catch (e: any) {
+      assert.include(e.message, "NotAbandoned");
+    }
+
+    const before = await provider.connection.getBalance(worker.publicKey);
+    await program.methods
+      .cancelTask()
+      .accounts({ poster: poster.publicKey, taskEscrow: idleTaskPda, taskBoard: tier1, agent: worker.publicKey })
+      .rpc();
+    const after = await provider.connection.getBalance(worker.publicKey);
+    assert.equal(after - before, bond);
+  });
+
+  it("records poster stats through the registry once a profile exists", async () => {
+    const profiled = await fundedKeypair(1);
+    const [profilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("poster"), profiled.publicKey.toBuffer()],
+      registry.programId
+    );
+    await registry.methods
+      .registerPoster()
+      .accounts({ posterAccount: profilePda, signer: profiled.publicKey, systemProgram: SystemProgram.programId })
+      .signers([profiled])
+      .rpc();
+
+    const statsTaskPda = await nextTaskPda(profiled.publicKey);
+    const init = (registryAccounts: object) =>
+      program.methods
+        .initializeTask("task-stats", new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Tracked task", SPEC_URI, sha256("stats"), new anchor.BN(0))
+        .accounts({
+          poster: profiled.publicKey,
+          posterNonce: noncePda(profiled.publicKey),
+          taskEscrow: statsTaskPda,
+          taskBoard: tier0,
+          systemProgram: SystemProgram.programId,
+          posterProfile: profilePda,
+          ...registryAccounts,
+        })
+        .signers([profiled]);
+
+    // Once the profile exists the poster can't skip recording by leaving out the CPI accounts
+    try {
+      await init({ escrowAuthority: null, registryProgram: null }).rpc();
+      assert.fail("Should have required the registry accounts");
+    } catch (e: any) {
+      assert.include(e.message, "MissingRegistryAccounts");
+    }
+
+    await init({ escrowAuthority, registryProgram: registry.programId }).rpc();
+    await program.methods
+      .cancelTask()
+      .accounts({
+        poster: profiled.publicKey,
+        taskEscrow: statsTaskPda,
+        taskBoard: tier0,
+        posterProfile: profilePda,
+        escrowAuthority,
+        registryProgram: registry.programId,
+      })
+      .signers([profiled])
+      .rpc();
+
+    const profile = await registry.account.posterAccount.fetch(profilePda);
+    assert.equal(profile.tasksPosted.toNumber(), 1);
+    assert.equal(profile.tasksCancelled.toNumber(), 1);
+    assert.equal(profile.cancelledAfterStart.toNumber(), 0);
+  });
+
+  // Session keys act for a registered agent; escrow tests use their own directory page
+  // so registry.ts can still create page 0.
+  const sessionAgent = anchor.web3.Keypair.generate();
+  const sessionPda = (key: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("session"), sessionAgent.publicKey.toBuffer(), key.toBuffer()],
+      registry.programId
+    )[0];
+
+  const createSession = async (key: PublicKey, allowed: number, ttlSecs: number, maxSpend: number) => {
+    const [agentAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), sessionAgent.publicKey.toBuffer()],
+      registry.programId
+    );
+    await registry.methods
+      .createSession(key, allowed, new anchor.BN(Math.floor(Date.now() / 1000) + ttlSecs), new anchor.BN(maxSpend))
+      .accounts({ agentAccount, session: sessionPda(key), signer: sessionAgent.publicKey, systemProgram: SystemProgram.programId })
+      .signers([sessionAgent])
+      .rpc();
+  };
+
+  const bondedTask = async (taskId: string, bond: number): Promise<PublicKey> => {
+    const pda = await nextTaskPda(poster.publicKey);
+    await program.methods
+      .initializeTask(taskId, new anchor.BN(0.05 * LAMPORTS_PER_SOL), "Session task", SPEC_URI, sha256(taskId), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: pda, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+    await program.methods
+      .setWorkerBond(new anchor.BN(bond))
+      .accounts({ poster: poster.publicKey, taskEscrow: pda })
+      .rpc();
+    return pda;
+  };
+
+  const submitWithSession = (task: PublicKey, key: anchor.web3.Keypair) =>
+    program.methods
+      .submitResult("https://example.com/session", sha256("session result"))
+      .accounts({
+        worker: key.publicKey,
+        sessionKey: sessionPda(key.publicKey),
+        taskEscrow: task,
+        taskBoard: tier0,
+        taskResult: resultPdaFor(task),
+        systemProgram: SystemProgram.programId,
+        escrowAuthority,
+        registryProgram: registry.programId,
+      })
+      .signers([key])
+      .rpc();
+
+  it("submits for an agent through a session key within its spend cap", async () => {
+    const airdrop = await provider.connection.requestAirdrop(sessionAgent.publicKey, LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    const [directory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("directory"), new anchor.BN(1000).toArrayLike(Buffer, "le", 4)],
+      registry.programId
+    );
+    await registry.methods
+      .initializeDirectoryPage(1000)
+      .accounts({ directory, payer: poster.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+    await registry.methods
+      .registerAgent("session-agent", "test-model", "https://github.com/ladymillard/chai-sol")
+      .accounts({
+        agentAccount: PublicKey.findProgramAddressSync([Buffer.from("agent"), sessionAgent.publicKey.toBuffer()], registry.programId)[0],
+        directory,
+        nameHandle: PublicKey.findProgramAddressSync(
+          [Buffer.from("handle"), Buffer.from("session-agent")],
+          registry.programId
+        )[0],
+        signer: sessionAgent.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([sessionAgent])
+      .rpc();
+
+    const key = await fundedKeypair(1);
+    const cap = 0.01 * LAMPORTS_PER_SOL;
+    await createSession(key.publicKey, 1 /* PERM_SUBMIT_RESULTS */, 3600, cap);
+
+    const task = await bondedTask("task-session", 0.006 * LAMPORTS_PER_SOL);
+    await submitWithSession(task, key);
+
+    const result = await program.account.taskResult.fetch(resultPdaFor(task));
+    assert.equal(result.worker.toBase58(), sessionAgent.publicKey.toBase58(), "the agent is credited, not the key");
+    const session = await registry.account.sessionKey.fetch(sessionPda(key.publicKey));
+    assert.equal(session.spent.toNumber(), 0.006 * LAMPORTS_PER_SOL);
+
+    // A second bond would take the session past its cap
+    const overCap = await bondedTask("task-session-cap", 0.006 * LAMPORTS_PER_SOL);
+    try {
+      await submitWithSession(overCap, key);
+      assert.fail("Should have enforced max_spend");
+    } catch (e: any) {
+      assert.include(e.message, "SpendLimitExceeded");
+    }
+  });
+
+  it("rejects session keys lacking the permission or past expiry", async () => {
+    const task = await bondedTask("task-session-denied", 0);
+
+    const metadataOnly = await fundedKeypair(0.5);
+    await createSession(metadataOnly.publicKey, 2 /* PERM_UPDATE_METADATA */, 3600, 0);
+    try {
+      await submitWithSession(task, metadataOnly);
+      assert.fail("Should have required PERM_SUBMIT_RESULTS");
+    } catch (e: any) {
+      assert.include(e.message, "MissingPermission");
+    }
+
+    const shortLived = await fundedKeypair(0.5);
+    await createSession(shortLived.publicKey, 1, 3, 0);
+    await new Promise((r) => setTimeout(r, 5000));
+    try {
+      await submitWithSession(task, shortLived);
+      assert.fail("Should have rejected an expired session");
+    } catch (e: any) {
+      assert.include(e.message, "SessionExpired");
+    }
+  });
+
+  it("records the verdict on the result and in the agent's reputation", async () => {
+    const [agentAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), sessionAgent.publicKey.toBuffer()],
+      registry.programId
+    );
+    const [agentDirectory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("directory"), new anchor.BN(1000).toArrayLike(Buffer, "le", 4)],
+      registry.programId
+    );
+    const task = await bondedTask("task-reputation", 0);
+    await program.methods
+      .submitResult("https://example.com/reputation", sha256("reputation result"))
+      .accounts({ worker: sessionAgent.publicKey, taskEscrow: task, taskBoard: tier0, taskResult: resultPdaFor(task), systemProgram: SystemProgram.programId })
+      .signers([sessionAgent])
+      .rpc();
+
+    const before = await registry.account.agentAccount.fetch(agentAccount);
+    const passing = verdict(10000);
+    await program.methods
+      .verifyResult(passing, sha256("task-reputation"))
+      .accounts({
+        ...verifyAccounts(task, sessionAgent.publicKey, tier0),
+        agentAccount,
+        agentDirectory,
+        escrowAuthority,
+        registryProgram: registry.programId,
+      })
+      .signers([oracle])
+      .rpc();
+
+    const result = await program.account.taskResult.fetch(resultPdaFor(task));
+    assert.deepEqual(result.status, { verified: {} });
+    assert.isTrue(result.verdict.passed);
+    assert.equal(result.verdict.score, passing.score);
+    assert.equal(result.verdict.payoutBps, passing.payoutBps);
+    assert.deepEqual(result.verdict.reason, { accepted: {} });
+    assert.deepEqual(result.verdict.reportHash, passing.reportHash);
+
+    // Reputation is a moving average weighting the new score 1/5
+    const after = await registry.account.agentAccount.fetch(agentAccount);
+    const expected = Math.floor((before.reputation * 4 + passing.score) / 5);
+    assert.equal(after.reputation, expected);
+    assert.equal(after.tasksCompleted.toNumber(), before.tasksCompleted.toNumber() + 1);
+    const directory = await registry.account.agentDirectory.fetch(agentDirectory);
+    const entry = directory.entries[after.directorySlot];
+    assert.equal(entry.wallet.toBase58(), sessionAgent.publicKey.toBase58());
+    assert.equal(entry.reputation, expected);
+  });
+
+  it("migrates a legacy task to the current layout", async () => {
+    // Preloaded from tests/fixtures/legacy-task.json (see Anchor.toml)
+    const legacyTask = new PublicKey("BGc3R1s3W1KoSR7kQTaKxgKEjmAS6bws1WKnacx6Cz1");
+    const before = await provider.connection.getAccountInfo(legacyTask);
+    assert.equal(before.data.length, 391);
+
+    await program.methods
+      .migrateTask()
+      .accounts({ taskEscrow: legacyTask, payer: poster.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const task = await program.account.taskEscrow.fetch(legacyTask);
+    assert.equal(task.version, 1);
+    assert.equal(task.poster.toBase58(), "HyyibnLMm3hiRDhfasyzaPVKLnLqrbsNhuQX1Yas1qMU");
+    assert.equal(task.taskId, "legacy-1");
+    assert.equal(task.title, "Audit the escrow program for rent-exemption edge cases before th");
+    assert.equal(task.bountyAmount.toNumber(), 500_000_000);
+    assert.equal(task.totalFunded.toNumber(), 500_000_000);
+    assert.deepEqual(task.status, { open: {} });
+    assert.isNull(task.assignedAgent);
+    assert.equal(task.createdAt.toNumber(), 1_700_000_000);
+    assert.equal(task.bump, 254);
+    assert.equal(task.specUri, "");
+
+    try {
+      await program.methods
+        .migrateTask()
+        .accounts({ taskEscrow: legacyTask, payer: poster.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+      assert.fail("Should have thrown AlreadyMigrated");
+    } catch (e: any) {
+      assert.include(e.message, "AlreadyMigrated");
+    }
+  });
+
+  it("gives tasks sharing a long task ID distinct addresses", async () => {
+    const UUID_TASK_ID = "6f1c2a9e-8b3d-4e57-9a0f-2c4d6e8f0a1b";
+    const first = await nextTaskPda(poster.publicKey);
+
+    await program.methods
+      .initializeTask(UUID_TASK_ID, new anchor.BN(0.01 * LAMPORTS_PER_SOL), "First", SPEC_URI, sha256("uuid"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: first, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const second = await nextTaskPda(poster.publicKey);
+    await program.methods
+      .initializeTask(UUID_TASK_ID, new anchor.BN(0.01 * LAMPORTS_PER_SOL), "Second", SPEC_URI, sha256("uuid"), new anchor.BN(0))
+      .accounts({ poster: poster.publicKey, posterNonce: posterNoncePda, taskEscrow: second, taskBoard: tier0, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const [a, b] = await Promise.all([
+      program.account.taskEscrow.fetch(first),
+      program.account.taskEscrow.fetch(second),
+    ]);
+    assert.notEqual(first.toBase58(), second.toBase58());
+    assert.equal(a.taskId, UUID_TASK_ID);
+    assert.equal(b.taskId, UUID_TASK_ID);
+    assert.equal(b.nonce.toNumber(), a.nonce.toNumber() + 1);
+  });
 });
@@ -14,6 +14,10 @@ describe("registry", () => {
   let configPda: PublicKey;
   let agentPda: PublicKey;
   let agentFreePda: PublicKey;
+  let directoryPda: PublicKey;
+
+  const handlePda = (handle: string) =>
+    PublicKey.findProgramAddressSync([Buffer.from("handle"), Buffer.from(handle)], program.programId)[0];
 
   before(async () => {
     [configPda] = PublicKey.findProgramAddressSync(
@@ -24,6 +28,10 @@ describe("registry", () => {
       [Buffer.from("agent"), agentWallet.publicKey.toBuffer()],
       program.programId
     );
+    [directoryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("directory"), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
     [agentFreePda] = PublicKey.findProgramAddressSync(
       [Buffer.from("agent"), agentWallet.publicKey.toBuffer()],
       program.programId
@@ -44,6 +52,21 @@ describe("registry", () => {
     assert.equal(config.admin.toBase58(), admin.publicKey.toBase58());
   });
 
+  it("creates the first agent directory page", async () => {
+    await program.methods
+      .initializeDirectoryPage(0)
+      .accounts({
+        directory: directoryPda,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const directory = await program.account.agentDirectory.fetch(directoryPda);
+    assert.equal(directory.page, 0);
+    assert.equal(directory.count, 0);
+  });
+
   it("registers an agent (self-funded)", async () => {
     // Airdrop to agentWallet so it can pay rent
     const sig = await provider.connection.requestAirdrop(
@@ -56,6 +79,8 @@ describe("registry", () => {
       .registerAgent("Kael", "claude-sonnet-4-6", "https://github.com/ladymillard/chai-sol")
       .accounts({
         agentAccount: agentPda,
+        directory: directoryPda,
+        nameHandle: handlePda("kael"),
         signer: agentWallet.publicKey,
         systemProgram: SystemProgram.programId,
       })
@@ -72,9 +97,10 @@ describe("registry", () => {
 
   it("verifies an agent via oracle (admin)", async () => {
     await program.methods
-      .verifyAgent(85, "Solana, TypeScript, Project Management")
+      .verifyAgent(85, "Solana, TypeScript, Project Management", new anchor.BN(0b101))
       .accounts({
         agentAccount: agentPda,
+        directory: directoryPda,
         registryConfig: configPda,
         admin: admin.publicKey,
       })
@@ -84,6 +110,13 @@ describe("registry", () => {
     assert.equal(account.verified, true);
     assert.equal(account.reputation, 85);
     assert.equal(account.specialties, "Solana, TypeScript, Project Management");
+
+    const directory = await program.account.agentDirectory.fetch(directoryPda);
+    const entry = directory.entries[account.directorySlot];
+    assert.equal(entry.wallet.toBase58(), agentWallet.publicKey.toBase58());
+    assert.equal(entry.reputation, 85);
+    assert.equal(entry.verified, 1);
+    assert.equal(entry.skills.toNumber(), 0b101);
   });
 
   it("updates agent metadata URL", async () => {
@@ -102,6 +135,244 @@ describe("registry", () => {
     assert.equal(account.metadataUrl, metadataUrl);
   });
 
+  it("lets a scoped operator key update metadata", async () => {
+    const operator = anchor.web3.Keypair.generate();
+    const [operatorPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("operator"), agentWallet.publicKey.toBuffer(), operator.publicKey.toBuffer()],
+      program.programId
+    );
+    const PERM_UPDATE_METADATA = 1 << 1;
+
+    await program.methods
+      .addOperator(operator.publicKey, PERM_UPDATE_METADATA, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+      .accounts({
+        agentAccount: agentPda,
+        operatorKey: operatorPda,
+        signer: agentWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([agentWallet])
+      .rpc();
+
+    const metadataUrl = "https://chai-sol.io/agents/kael/v2.json";
+    await program.methods
+      .updateAgent(metadataUrl)
+      .accounts({
+        agentAccount: agentPda,
+        signer: operator.publicKey,
+        operatorKey: operatorPda,
+      })
+      .signers([operator])
+      .rpc();
+
+    const account = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(account.metadataUrl, metadataUrl);
+  });
+
+  it("binds one handle to the agent named after it", async () => {
+    const kaelHandle = handlePda("kael");
+
+    await program.methods
+      .claimHandle("kael")
+      .accounts({
+        agentAccount: agentPda,
+        handleAccount: kaelHandle,
+        signer: agentWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([agentWallet])
+      .rpc();
+
+    const handle = await program.account.handleAccount.fetch(kaelHandle);
+    assert.equal(handle.owner.toBase58(), agentWallet.publicKey.toBase58());
+    assert.isTrue((await program.account.agentAccount.fetch(agentPda)).handleClaimed);
+
+    // Same normalized handle, same PDA: the init itself must refuse
+    try {
+      await program.methods
+        .claimHandle("kael")
+        .accounts({
+          agentAccount: agentPda,
+          handleAccount: kaelHandle,
+          signer: agentWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agentWallet])
+        .rpc();
+      assert.fail("Should have rejected a duplicate claim");
+    } catch (e: any) {
+      assert.include(e.toString(), "already in use");
+    }
+
+    // A second handle for the same wallet
+    try {
+      await program.methods
+        .claimHandle("opus")
+        .accounts({
+          agentAccount: agentPda,
+          handleAccount: handlePda("opus"),
+          signer: agentWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agentWallet])
+        .rpc();
+      assert.fail("Should have thrown HandleAlreadyHeld");
+    } catch (e: any) {
+      assert.include(e.message, "HandleAlreadyHeld");
+    }
+
+    // Another wallet registering under the claimed name
+    const impostor = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(impostor.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+    try {
+      await program.methods
+        .registerAgent("KAEL", "claude-sonnet-4-6", "https://github.com/ladymillard/chai-sol")
+        .accounts({
+          agentAccount: PublicKey.findProgramAddressSync(
+            [Buffer.from("agent"), impostor.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          directory: directoryPda,
+          nameHandle: kaelHandle,
+          signer: impostor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([impostor])
+        .rpc();
+      assert.fail("Should have thrown HandleTaken");
+    } catch (e: any) {
+      assert.include(e.message, "HandleTaken");
+    }
+
+    // Handing the handle to another agent swaps their names along with it
+    const successor = anchor.web3.Keypair.generate();
+    const successorPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), successor.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    const fund = await provider.connection.requestAirdrop(successor.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(fund);
+    await program.methods
+      .registerAgent("Vela", "gpt-5", "https://github.com/ladymillard/chai-sol")
+      .accounts({
+        agentAccount: successorPda,
+        directory: directoryPda,
+        nameHandle: handlePda("vela"),
+        signer: successor.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([successor])
+      .rpc();
+    const transfer = (from: anchor.web3.Keypair, fromPda: PublicKey, to: anchor.web3.Keypair, toPda: PublicKey) =>
+      program.methods
+        .transferHandle()
+        .accounts({ handleAccount: kaelHandle, owner: from.publicKey, ownerAgent: fromPda, newOwner: to.publicKey, newOwnerAgent: toPda })
+        .signers([from, to])
+        .rpc();
+
+    await transfer(agentWallet, agentPda, successor, successorPda);
+    assert.equal((await program.account.handleAccount.fetch(kaelHandle)).owner.toBase58(), successor.publicKey.toBase58());
+    let previous = await program.account.agentAccount.fetch(agentPda);
+    let next = await program.account.agentAccount.fetch(successorPda);
+    assert.equal(next.name, "Kael");
+    assert.isTrue(next.handleClaimed);
+    assert.equal(previous.name, "Vela");
+    assert.isFalse(previous.handleClaimed);
+
+    // And back, so the original agent holds it again
+    await transfer(successor, successorPda, agentWallet, agentPda);
+    previous = await program.account.agentAccount.fetch(successorPda);
+    next = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(next.name, "Kael");
+    assert.equal(previous.name, "Vela");
+
+    // Released so the profile can be closed later on
+    await program.methods
+      .releaseHandle()
+      .accounts({ handleAccount: kaelHandle, owner: agentWallet.publicKey, ownerAgent: agentPda })
+      .signers([agentWallet])
+      .rpc();
+    assert.isFalse((await program.account.agentAccount.fetch(agentPda)).handleClaimed);
+  });
+
+  it("refuses to migrate an agent already on the current layout", async () => {
+    try {
+      await program.methods
+        .migrateAgent()
+        .accounts({
+          agentAccount: agentPda,
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have rejected a current-layout account");
+    } catch (e: any) {
+      assert.include(e.message, "AlreadyMigrated");
+    }
+  });
+
+  it("migrates a legacy agent to the current layout", async () => {
+    // Preloaded from tests/fixtures/legacy-agent.json (see Anchor.toml)
+    const legacyAgent = new PublicKey("AUmiyZdFFpraPLXbqH16gfQ4dJczZZZc3FE28mgpqt38");
+    const before = await provider.connection.getAccountInfo(legacyAgent);
+    assert.equal(before.data.length, 766);
+
+    await program.methods
+      .migrateAgent()
+      .accounts({ agentAccount: legacyAgent, payer: admin.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(legacyAgent);
+    assert.equal(after.data.length, 855);
+    assert.equal(after.lamports, await provider.connection.getMinimumBalanceForRentExemption(after.data.length));
+
+    const agent = await program.account.agentAccount.fetch(legacyAgent);
+    assert.equal(agent.version, 1);
+    assert.equal(agent.wallet.toBase58(), "HNWrsfDNhSiauhYPVxaPAQLdsC8QvZj8hV2v42oJQenB");
+    assert.equal(agent.name, "Orion");
+    assert.equal(agent.model, "gpt-4");
+    assert.equal(agent.specialties, "rust,solana");
+    assert.equal(agent.githubUrl, "https://github.com/orion-agent");
+    assert.equal(agent.metadataUrl, "https://example.com/orion.json");
+    assert.equal(agent.tasksCompleted.toNumber(), 12);
+    assert.equal(agent.totalEarned.toNumber(), 3_000_000_000);
+    assert.equal(agent.reputation, 87);
+    assert.isTrue(agent.verified);
+    assert.equal(agent.registeredAt.toNumber(), 1_700_000_000);
+    assert.equal(agent.stake.toNumber(), 0);
+    assert.equal(agent.unstaking.toNumber(), 0);
+    assert.equal(agent.skills.toNumber(), 0);
+    assert.isFalse(agent.listed);
+    assert.isFalse(agent.handleClaimed);
+
+    try {
+      await program.methods
+        .migrateAgent()
+        .accounts({ agentAccount: legacyAgent, payer: admin.publicKey, systemProgram: SystemProgram.programId })
+        .rpc();
+      assert.fail("Should have thrown AlreadyMigrated");
+    } catch (e: any) {
+      assert.include(e.message, "AlreadyMigrated");
+    }
+  });
+
+  it("refuses to migrate a config already on the current layout", async () => {
+    try {
+      await program.methods
+        .migrateConfig()
+        .accounts({
+          registryConfig: configPda,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have rejected a current-layout config");
+    } catch (e: any) {
+      assert.include(e.message, "AlreadyMigrated");
+    }
+  });
+
   it("rejects verify_agent from non-admin", async () => {
     const impostor = anchor.web3.Keypair.generate();
     const sig = await provider.connection.requestAirdrop(
@@ -112,9 +383,10 @@ describe("registry", () => {
 
     try {
       await program.methods
-        .verifyAgent(99, "Hacker")
+        .verifyAgent(99, "Hacker", new anchor.BN(0))
         .accounts({
           agentAccount: agentPda,
+          directory: directoryPda,
           registryConfig: configPda,
           admin: impostor.publicKey,
         })
@@ -126,6 +398,142 @@ describe("registry", () => {
     }
   });
 
+  it("only lets the escrow authority record verdicts", async () => {
+    try {
+      await program.methods
+        .recordVerdict(100, true)
+        .accounts({
+          agentAccount: agentPda,
+          directory: directoryPda,
+          authority: admin.publicKey,
+        })
+        .rpc();
+      assert.fail("Should have rejected a non-escrow signer");
+    } catch (e: any) {
+      assert.include(e.message, "Unauthorized");
+    }
+  });
+
+  it("stakes SOL and starts the unstake cooldown", async () => {
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_vault")],
+      program.programId
+    );
+    await program.methods
+      .initializeStakeVault()
+      .accounts({
+        stakeVault: vaultPda,
+        registryConfig: configPda,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const stakeAccounts = {
+      agentAccount: agentPda,
+      stakeVault: vaultPda,
+      signer: agentWallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods
+      .stake(new anchor.BN(0.2 * anchor.web3.LAMPORTS_PER_SOL))
+      .accounts(stakeAccounts)
+      .signers([agentWallet])
+      .rpc();
+
+    let account = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(account.stake.toNumber(), 0.2 * anchor.web3.LAMPORTS_PER_SOL);
+
+    await program.methods
+      .requestUnstake(account.stake)
+      .accounts(stakeAccounts)
+      .signers([agentWallet])
+      .rpc();
+
+    account = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(account.stake.toNumber(), 0);
+    assert.equal(account.unstaking.toNumber(), 0.2 * anchor.web3.LAMPORTS_PER_SOL);
+    assert.isAbove(account.unstakeAvailableAt.toNumber(), Math.floor(Date.now() / 1000));
+
+    // Clear it so the account can be closed below
+    await program.methods
+      .slashAgent(account.unstaking)
+      .accounts({
+        agentAccount: agentPda,
+        stakeVault: vaultPda,
+        registryConfig: configPda,
+        authority: admin.publicKey,
+        recipient: admin.publicKey,
+      })
+      .rpc();
+  });
+
+  it("enforces the per-epoch sponsorship budget on free registration", async () => {
+    const freeWallets = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const freePdas = freeWallets.map((w) =>
+      PublicKey.findProgramAddressSync([Buffer.from("agent"), w.publicKey.toBuffer()], program.programId)[0]
+    );
+
+    await program.methods
+      .setSponsorshipBudget(1, new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ registryConfig: configPda, admin: admin.publicKey })
+      .rpc();
+
+    await program.methods
+      .registerAgentFree("Nova", "gemini-3-pro", "https://github.com/ladymillard/chai-sol")
+      .accounts({
+        agentAccount: freePdas[0],
+        directory: directoryPda,
+        registryConfig: configPda,
+        admin: admin.publicKey,
+        agentWallet: freeWallets[0].publicKey,
+        nameHandle: handlePda("nova"),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([freeWallets[0]])
+      .rpc();
+
+    try {
+      await program.methods
+        .registerAgentFree("Zara", "claude-sonnet-4", "https://github.com/ladymillard/chai-sol")
+        .accounts({
+          agentAccount: freePdas[1],
+          directory: directoryPda,
+          registryConfig: configPda,
+          admin: admin.publicKey,
+          agentWallet: freeWallets[1].publicKey,
+          nameHandle: handlePda("zara"),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([freeWallets[1]])
+        .rpc();
+      assert.fail("Should have thrown SponsorshipBudgetExceeded");
+    } catch (e: any) {
+      assert.include(e.message, "SponsorshipBudgetExceeded");
+    }
+  });
+
+  it("registers a poster profile with empty stats", async () => {
+    const [posterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("poster"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .registerPoster()
+      .accounts({
+        posterAccount: posterPda,
+        signer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const poster = await program.account.posterAccount.fetch(posterPda);
+    assert.equal(poster.wallet.toBase58(), admin.publicKey.toBase58());
+    assert.equal(poster.tasksPosted.toNumber(), 0);
+    assert.equal(poster.tasksCancelled.toNumber(), 0);
+  });
+
   it("closes agent account, rent returned to admin", async () => {
     const adminBalanceBefore = await provider.connection.getBalance(admin.publicKey);
 
@@ -133,6 +541,7 @@ describe("registry", () => {
       .closeAgent()
       .accounts({
         agentAccount: agentPda,
+        directory: directoryPda,
         registryConfig: configPda,
         admin: admin.publicKey,
       })